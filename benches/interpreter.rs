@@ -0,0 +1,51 @@
+// measures raw interpreter speed: instructions per second on a flat memory, no bus or I/O
+// run with `cargo bench`
+use std::time::Instant;
+
+use redNES::cpu::CPU;
+use redNES::memory::FlatMemory;
+
+const INSTRUCTIONS: u64 = 20_000_000;
+
+// a loop mixing loads, stores, arithmetic, indexing, read-modify-write and branches
+const PROGRAM: [u8; 24] = [
+    0xA2, 0x00,       // start: LDX #$00
+    0xBD, 0x00, 0x02, // loop:  LDA $0200,X
+    0x69, 0x01,       //        ADC #$01
+    0x9D, 0x00, 0x02, //        STA $0200,X
+    0x45, 0x10,       //        EOR $10
+    0x85, 0x10,       //        STA $10
+    0x1E, 0x00, 0x03, //        ASL $0300,X
+    0x48,             //        PHA
+    0x68,             //        PLA
+    0xE8,             //        INX
+    0xD0, 0xEC,       //        BNE loop
+    0xF0, 0xE8,       //        BEQ start
+];
+
+fn main() {
+    let mut memory = FlatMemory::new();
+    memory.load(0x8000, &PROGRAM);
+    memory.load(0xFFFC, &[0x00, 0x80]);
+
+    let mut cpu = CPU::new(memory);
+    cpu.reset();
+
+    let mut executed: u64 = 0;
+    let start = Instant::now();
+    cpu.run_until(|_, _| {
+        executed += 1;
+        executed == INSTRUCTIONS
+    })
+    .expect("benchmark program should not fault");
+    let elapsed = start.elapsed();
+
+    let per_second = executed as f64 / elapsed.as_secs_f64();
+    println!(
+        "interpreter: {} instructions in {:.3?} ({:.1} M instructions/s, {} cycles)",
+        executed,
+        elapsed,
+        per_second / 1_000_000.0,
+        cpu.cycles
+    );
+}
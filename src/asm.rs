@@ -0,0 +1,390 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::cpu::{AddressingMode, Memory};
+use crate::opcodes::{self, Instruction};
+
+// a small two pass 6502 assembler for tests and the debugger
+//
+//         .org $0600
+// SCREEN = $0200
+// start:  LDX #$00
+// loop:   LDA message,X
+//         STA SCREEN,X
+//         INX
+//         CPX #5
+//         BNE loop
+//         BRK
+// message: .byte $48, $45, $4C, $4C, $4F
+//
+// numbers are $hex, %binary or decimal, operands may use labels with +/- offsets
+// and #<label / #>label pick the low or high byte
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsmError {
+    pub line: usize, // 1 based
+    pub message: String,
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+// a run of bytes that starts at `origin`, each .org starts a new one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub origin: u16,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Program {
+    pub segments: Vec<Segment>,
+    pub labels: HashMap<String, u16>,
+}
+
+impl Program {
+    // writes every segment through the memory interface, e.g. to patch a running program
+    pub fn write_to<M: Memory>(&self, memory: &mut M) {
+        for segment in &self.segments {
+            for (i, byte) in segment.bytes.iter().enumerate() {
+                memory.mem_write(segment.origin.wrapping_add(i as u16), *byte);
+            }
+        }
+    }
+
+    pub fn label(&self, name: &str) -> Option<u16> {
+        self.labels.get(name).copied()
+    }
+}
+
+pub fn assemble(source: &str) -> Result<Program, AsmError> {
+    assemble_at(source, 0x0000)
+}
+
+// assembles with `origin` as the address used until the first .org
+pub fn assemble_at(source: &str, origin: u16) -> Result<Program, AsmError> {
+    let mut assembler = Assembler {
+        labels: HashMap::new(),
+        statements: Vec::new(),
+    };
+    assembler.first_pass(source, origin)?;
+    assembler.second_pass()
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Number(u16),
+    Label(String),
+    Offset(Box<Expr>, i32),
+    LowByte(Box<Expr>),
+    HighByte(Box<Expr>),
+}
+
+enum Statement {
+    Org(u16),
+    Bytes(Vec<Expr>),
+    Words(Vec<Expr>),
+    Instruction {
+        instruction: &'static Instruction,
+        operand: Option<Expr>,
+    },
+}
+
+struct Assembler {
+    labels: HashMap<String, u16>,
+    statements: Vec<(usize, u16, Statement)>, // line, address, statement
+}
+
+impl Assembler {
+    // parses every line, fixing instruction sizes and label addresses
+    fn first_pass(&mut self, source: &str, origin: u16) -> Result<(), AsmError> {
+        let mut pc = origin;
+        for (index, raw_line) in source.lines().enumerate() {
+            let line_no = index + 1;
+            let err = |message: String| AsmError { line: line_no, message };
+
+            let mut line = match raw_line.find(';') {
+                Some(comment) => &raw_line[..comment],
+                None => raw_line,
+            }
+            .trim();
+
+            // NAME = value
+            if let Some((name, value)) = line.split_once('=') {
+                let name = name.trim();
+                if is_identifier(name) {
+                    let expr = parse_expr(value.trim()).map_err(err)?;
+                    let value = self.eval(&expr).ok_or_else(|| {
+                        err(format!("'{}' must be defined before it is used in a constant", value.trim()))
+                    })?;
+                    self.define(name, value, line_no)?;
+                    continue;
+                }
+            }
+
+            // label:
+            if let Some((label, rest)) = line.split_once(':') {
+                let label = label.trim();
+                if !is_identifier(label) {
+                    return Err(err(format!("invalid label '{}'", label)));
+                }
+                self.define(label, pc, line_no)?;
+                line = rest.trim();
+            }
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let (word, rest) = match line.split_once(char::is_whitespace) {
+                Some((word, rest)) => (word, rest.trim()),
+                None => (line, ""),
+            };
+
+            let statement = match word.to_ascii_lowercase().as_str() {
+                ".org" => {
+                    let expr = parse_expr(rest).map_err(err)?;
+                    pc = self.eval(&expr).ok_or_else(|| err(".org needs a known address".to_string()))?;
+                    Statement::Org(pc)
+                }
+                ".byte" | ".db" => Statement::Bytes(parse_list(rest).map_err(err)?),
+                ".word" | ".dw" => Statement::Words(parse_list(rest).map_err(err)?),
+                _ => self.parse_instruction(word, rest).map_err(err)?,
+            };
+
+            let size = match &statement {
+                Statement::Org(_) => 0,
+                Statement::Bytes(values) => values.len(),
+                Statement::Words(values) => values.len() * 2,
+                Statement::Instruction { instruction, .. } => instruction.len as usize,
+            };
+            self.statements.push((line_no, pc, statement));
+            pc = pc.wrapping_add(size as u16);
+        }
+        Ok(())
+    }
+
+    // encodes every statement now that all labels are known
+    fn second_pass(&self) -> Result<Program, AsmError> {
+        let mut segments: Vec<Segment> = Vec::new();
+        for (line_no, address, statement) in &self.statements {
+            let err = |message: String| AsmError { line: *line_no, message };
+            let resolve = |expr: &Expr| {
+                self.eval(expr)
+                    .ok_or_else(|| err(format!("undefined label in '{}'", describe(expr))))
+            };
+
+            let mut bytes = Vec::new();
+            match statement {
+                Statement::Org(origin) => {
+                    segments.push(Segment { origin: *origin, bytes: Vec::new() });
+                    continue;
+                }
+                Statement::Bytes(values) => {
+                    for value in values {
+                        let value = resolve(value)?;
+                        if value > 0xFF {
+                            return Err(err(format!("${:04X} does not fit in a byte", value)));
+                        }
+                        bytes.push(value as u8);
+                    }
+                }
+                Statement::Words(values) => {
+                    for value in values {
+                        let value = resolve(value)?;
+                        bytes.push((value & 0xFF) as u8);
+                        bytes.push((value >> 8) as u8);
+                    }
+                }
+                Statement::Instruction { instruction, operand } => {
+                    bytes.push(instruction.code);
+                    if let Some(operand) = operand {
+                        let value = resolve(operand)?;
+                        match instruction.mode {
+                            AddressingMode::Relative => {
+                                let offset = value as i32 - (address.wrapping_add(2) as i32);
+                                if !(-128..=127).contains(&offset) {
+                                    return Err(err(format!("branch to ${:04X} is out of range", value)));
+                                }
+                                bytes.push(offset as i8 as u8);
+                            }
+                            _ if instruction.len == 2 => {
+                                if value > 0xFF {
+                                    return Err(err(format!("${:04X} does not fit in a byte", value)));
+                                }
+                                bytes.push(value as u8);
+                            }
+                            _ => {
+                                bytes.push((value & 0xFF) as u8);
+                                bytes.push((value >> 8) as u8);
+                            }
+                        }
+                    }
+                }
+            }
+
+            // start a new segment unless these bytes carry on from the previous ones
+            let continues = segments
+                .last()
+                .is_some_and(|segment| segment.origin.wrapping_add(segment.bytes.len() as u16) == *address);
+            if !continues {
+                segments.push(Segment { origin: *address, bytes: Vec::new() });
+            }
+            segments.last_mut().unwrap().bytes.extend(bytes);
+        }
+
+        segments.retain(|segment| !segment.bytes.is_empty());
+        Ok(Program {
+            segments,
+            labels: self.labels.clone(),
+        })
+    }
+
+    fn define(&mut self, name: &str, value: u16, line: usize) -> Result<(), AsmError> {
+        if self.labels.insert(name.to_string(), value).is_some() {
+            return Err(AsmError { line, message: format!("'{}' is defined twice", name) });
+        }
+        Ok(())
+    }
+
+    fn eval(&self, expr: &Expr) -> Option<u16> {
+        match expr {
+            Expr::Number(value) => Some(*value),
+            Expr::Label(name) => self.labels.get(name).copied(),
+            Expr::Offset(expr, offset) => Some((self.eval(expr)? as i32).wrapping_add(*offset) as u16),
+            Expr::LowByte(expr) => Some(self.eval(expr)? & 0xFF),
+            Expr::HighByte(expr) => Some(self.eval(expr)? >> 8),
+        }
+    }
+
+    // picks the opcode from the operand syntax, zero page forms only for values already known to fit
+    fn parse_instruction(&self, mnemonic: &str, operand: &str) -> Result<Statement, String> {
+        let mnemonic = mnemonic.to_ascii_uppercase();
+        if !opcodes::CPU_INSTRUCTIONS.iter().any(|i| i.mnemonic == mnemonic) {
+            return Err(format!("unknown instruction '{}'", mnemonic));
+        }
+        let find = |mode: AddressingMode| find_instruction(&mnemonic, mode);
+
+        let upper = operand.to_ascii_uppercase();
+        let (modes, expr): (Vec<AddressingMode>, Option<Expr>) = if operand.is_empty() {
+            (vec![AddressingMode::NonAddressing, AddressingMode::Accumulator], None)
+        } else if upper == "A" {
+            (vec![AddressingMode::Accumulator], None)
+        } else if let Some(value) = operand.strip_prefix('#') {
+            (vec![AddressingMode::Immediate], Some(parse_expr(value.trim())?))
+        } else if upper.starts_with('(') && upper.ends_with(",X)") {
+            let inner = &operand[1..operand.len() - 3];
+            (vec![AddressingMode::IndirectX], Some(parse_expr(inner.trim())?))
+        } else if upper.starts_with('(') && upper.replace(' ', "").ends_with("),Y") {
+            let close = operand.rfind(')').unwrap();
+            (vec![AddressingMode::IndirectY], Some(parse_expr(operand[1..close].trim())?))
+        } else if upper.starts_with('(') && upper.ends_with(')') {
+            let inner = &operand[1..operand.len() - 1];
+            (vec![AddressingMode::Indirect], Some(parse_expr(inner.trim())?))
+        } else {
+            let (value, zero_page, absolute) = if let Some(value) = strip_index(operand, 'X') {
+                (value, AddressingMode::ZeroPageX, AddressingMode::AbsoluteX)
+            } else if let Some(value) = strip_index(operand, 'Y') {
+                (value, AddressingMode::ZeroPageY, AddressingMode::AbsoluteY)
+            } else {
+                (operand, AddressingMode::ZeroPage, AddressingMode::Absolute)
+            };
+            let expr = parse_expr(value)?;
+            let fits_zero_page = self.eval(&expr).is_some_and(|value| value <= 0xFF);
+            let modes = if find(AddressingMode::Relative).is_some() {
+                vec![AddressingMode::Relative]
+            } else if fits_zero_page {
+                vec![zero_page, absolute]
+            } else {
+                vec![absolute]
+            };
+            (modes, Some(expr))
+        };
+
+        match modes.into_iter().find_map(find) {
+            Some(instruction) => Ok(Statement::Instruction { instruction, operand: expr }),
+            None => Err(format!("{} does not support the operand '{}'", mnemonic, operand)),
+        }
+    }
+}
+
+// official opcodes win over unofficial duplicates (SBC #imm, the NOPs)
+fn find_instruction(mnemonic: &str, mode: AddressingMode) -> Option<&'static Instruction> {
+    let mut candidates = opcodes::CPU_INSTRUCTIONS
+        .iter()
+        .filter(|i| i.mnemonic == mnemonic && i.mode == mode);
+    let first = candidates.next()?;
+    Some(if first.unofficial {
+        candidates.find(|i| !i.unofficial).unwrap_or(first)
+    } else {
+        first
+    })
+}
+
+// "value,X" -> "value"
+fn strip_index(operand: &str, register: char) -> Option<&str> {
+    let (value, index) = operand.rsplit_once(',')?;
+    if index.trim().eq_ignore_ascii_case(&register.to_string()) {
+        Some(value.trim())
+    } else {
+        None
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_list(text: &str) -> Result<Vec<Expr>, String> {
+    text.split(',').map(|value| parse_expr(value.trim())).collect()
+}
+
+fn parse_expr(text: &str) -> Result<Expr, String> {
+    if let Some(rest) = text.strip_prefix('<') {
+        return Ok(Expr::LowByte(Box::new(parse_expr(rest.trim())?)));
+    }
+    if let Some(rest) = text.strip_prefix('>') {
+        return Ok(Expr::HighByte(Box::new(parse_expr(rest.trim())?)));
+    }
+
+    // label+1, label-2
+    if let Some(split) = text.rfind(['+', '-']).filter(|&i| i > 0) {
+        let base = parse_expr(text[..split].trim())?;
+        let offset = parse_number(text[split + 1..].trim())? as i32;
+        let offset = if text.as_bytes()[split] == b'-' { -offset } else { offset };
+        return Ok(Expr::Offset(Box::new(base), offset));
+    }
+
+    if is_identifier(text) {
+        return Ok(Expr::Label(text.to_string()));
+    }
+    Ok(Expr::Number(parse_number(text)?))
+}
+
+fn parse_number(text: &str) -> Result<u16, String> {
+    let parsed = if let Some(hex) = text.strip_prefix('$') {
+        u16::from_str_radix(hex, 16)
+    } else if let Some(binary) = text.strip_prefix('%') {
+        u16::from_str_radix(binary, 2)
+    } else {
+        text.parse::<u16>()
+    };
+    parsed.map_err(|_| format!("invalid number '{}'", text))
+}
+
+fn describe(expr: &Expr) -> String {
+    match expr {
+        Expr::Number(value) => format!("${:04X}", value),
+        Expr::Label(name) => name.clone(),
+        Expr::Offset(expr, offset) => format!("{}{:+}", describe(expr), offset),
+        Expr::LowByte(expr) => format!("<{}", describe(expr)),
+        Expr::HighByte(expr) => format!(">{}", describe(expr)),
+    }
+}
@@ -1,72 +1,206 @@
+use std::fmt;
+
 use crate::cartridge::Rom;
 use crate::cpu::Memory;
+use crate::error::EmulatorError;
+use crate::joypad::Joypad;
+use crate::mapper::{self, Mapper};
 
 const RAM: u16 = 0x0000;
 const RAM_MIRRORS_END: u16 = 0x1FFF;
 const PPU_REGISTERS: u16 = 0x2000;
 const PPU_MIRRORS_END: u16 = 0x3FFF;
+const APU_REGISTERS: u16 = 0x4000;
+const APU_REGISTERS_END: u16 = 0x4013;
+const OAM_DMA: u16 = 0x4014;
+const APU_STATUS: u16 = 0x4015;
+const JOYPAD_1: u16 = 0x4016;
+const JOYPAD_2: u16 = 0x4017; // APU frame counter when written
+const APU_TEST_END: u16 = 0x401F;
+const CARTRIDGE: u16 = 0x4020; // expansion, PRG-RAM and PRG-ROM all belong to the mapper
+
+const OAM_ADDR: u16 = 0x2003;
+const OAM_DATA: u16 = 0x2004;
+
+// accesses that nothing on the bus answered, reported to the log sink
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BusEvent {
+    UnmappedRead(u16),
+    UnmappedWrite(u16, u8),
+}
+
+impl fmt::Display for BusEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BusEvent::UnmappedRead(address) => write!(f, "unmapped read at ${:04X}", address),
+            BusEvent::UnmappedWrite(address, value) => {
+                write!(f, "unmapped write of ${:02X} at ${:04X}", value, address)
+            }
+        }
+    }
+}
 
 impl Memory for Bus {
-    fn mem_read(&self, address: u16) -> u8 {
-        match address {
+    fn mem_read(&mut self, address: u16) -> u8 {
+        let value = match address {
             RAM ..= RAM_MIRRORS_END => {
                 let mirror_down_address = address & 0b00000111_11111111;
                 self.cpu_vram[mirror_down_address as usize]
             }
             PPU_REGISTERS ..= PPU_MIRRORS_END => {
-                let _mirror_down_address = address & 0b00100000_00000111;
-                todo!("PPU not implemented")
+                match address & 0b00100000_00000111 {
+                    OAM_DATA => self.oam_data[self.oam_addr as usize],
+                    // the rest of the PPU doesn't exist yet
+                    _ => self.open_bus,
+                }
             }
-            0x8000..=0xFFFF => {
-                self.read_rpg_rom(address)
+            APU_STATUS => {
+                // no APU yet, so no channel is playing; bit 5 is not driven and reading
+                // $4015 does not update the external data bus
+                return self.open_bus & 0b0010_0000;
             }
-            _ => {
-                print!("Ignoring memory access at {}", address);
-                0
+            // only the low bit comes from the controller, the rest is what was left on the bus
+            JOYPAD_1 => (self.open_bus & 0b1110_0000) | self.joypads[0].read(),
+            JOYPAD_2 => (self.open_bus & 0b1110_0000) | self.joypads[1].read(),
+            APU_REGISTERS ..= OAM_DMA | 0x4018 ..= APU_TEST_END => {
+                // write-only registers
+                self.open_bus
             }
+            CARTRIDGE ..= 0xFFFF => match self.mapper.cpu_read(address) {
+                Some(value) => value,
+                None => {
+                    self.log(BusEvent::UnmappedRead(address));
+                    self.open_bus
+                }
+            },
+        };
+        self.open_bus = value;
+        value
+    }
+
+    fn peek(&self, address: u16) -> u8 {
+        match address {
+            RAM ..= RAM_MIRRORS_END => {
+                let mirror_down_address = address & 0b00000111_11111111;
+                self.cpu_vram[mirror_down_address as usize]
+            }
+            PPU_REGISTERS ..= PPU_MIRRORS_END => match address & 0b00100000_00000111 {
+                OAM_DATA => self.oam_data[self.oam_addr as usize],
+                _ => self.open_bus,
+            },
+            APU_STATUS => self.open_bus & 0b0010_0000,
+            JOYPAD_1 => (self.open_bus & 0b1110_0000) | self.joypads[0].peek(),
+            JOYPAD_2 => (self.open_bus & 0b1110_0000) | self.joypads[1].peek(),
+            CARTRIDGE ..= 0xFFFF => self.mapper.cpu_peek(address).unwrap_or(self.open_bus),
+            _ => self.open_bus,
         }
     }
 
     fn mem_write(&mut self, address: u16, value: u8) {
+        self.open_bus = value;
         match address {
             RAM ..=RAM_MIRRORS_END => {
                 let mirror_down_address = address & 0b11111111111;
                 self.cpu_vram[mirror_down_address as usize] = value;
             }
             PPU_REGISTERS ..= PPU_MIRRORS_END => {
-                let _mirror_down_address = address & 0b00100000_00000111;
-                todo!("PPU not implemented")
+                match address & 0b00100000_00000111 {
+                    OAM_ADDR => self.oam_addr = value,
+                    OAM_DATA => {
+                        self.oam_data[self.oam_addr as usize] = value;
+                        self.oam_addr = self.oam_addr.wrapping_add(1);
+                    }
+                    // the rest of the PPU doesn't exist yet, so its register writes are dropped
+                    _ => {}
+                }
+                self.mapper.ppu_register_write(address & 0b00100000_00000111, value);
             }
-            0x8000..=0xFFFF => {
-                panic!("Attempting to write to ROM")
+            OAM_DMA => self.oam_dma_page = Some(value),
+            JOYPAD_1 => {
+                // one strobe line is shared by both controller ports
+                self.joypads[0].write(value);
+                self.joypads[1].write(value);
             }
-            _ => {
-                print!("Ignoring memory access at {}", address);
+            APU_REGISTERS ..= APU_REGISTERS_END | APU_STATUS | JOYPAD_2 => {
+                // no APU yet, so its register writes are dropped
             }
+            0x4018 ..= APU_TEST_END => {
+                // the APU test registers are disabled on retail consoles
+                self.log(BusEvent::UnmappedWrite(address, value));
+            }
+            CARTRIDGE ..= 0xFFFF => self.mapper.cpu_write(address, value),
         }
     }
+
+    fn tick(&mut self) {
+        self.mapper.cpu_clock();
+    }
+
+    fn irq_line(&self) -> bool {
+        self.mapper.irq_pending()
+    }
+
+    fn take_oam_dma(&mut self) -> Option<u8> {
+        self.oam_dma_page.take()
+    }
 }
 
 
 pub struct Bus {
     cpu_vram: [u8; 2048],
-    rom: Rom
+    mapper: Box<dyn Mapper>,
+    oam_addr: u8,
+    oam_data: [u8; 256], // sprite memory, held here until there is a PPU to own it
+    oam_dma_page: Option<u8>, // set by a write to $4014 until the cpu runs the transfer
+    joypads: [Joypad; 2],
+    open_bus: u8, // last value driven on the data bus, returned by reads nothing answers
+    log_sink: Option<Box<dyn FnMut(BusEvent)>>,
 }
 
 impl Bus {
-    pub fn new(rom: Rom) -> Self {
+    // plugs in the cartridge, failing if its mapper isn't supported
+    pub fn new(rom: Rom) -> Result<Self, EmulatorError> {
+        Ok(Bus::with_mapper(mapper::from_rom(rom)?))
+    }
+
+    pub fn with_mapper(mapper: Box<dyn Mapper>) -> Self {
         Bus {
             cpu_vram: [0; 2048],
-            rom: rom,
+            mapper,
+            oam_addr: 0,
+            oam_data: [0; 256],
+            oam_dma_page: None,
+            joypads: [Joypad::new(), Joypad::new()],
+            open_bus: 0,
+            log_sink: None,
         }
     }
 
-    fn read_rpg_rom(&self, mut addr: u16) -> u8 {
-        addr -= 0x8000;
-        if (self.rom.prg_rom.len() == 0x4000 && addr >= 0x4000) {
-            // mirror
-            addr = addr % 0x4000;
+    // routes unmapped accesses to `sink`; without one they are silently ignored
+    pub fn set_log_sink<F: FnMut(BusEvent) + 'static>(&mut self, sink: F) {
+        self.log_sink = Some(Box::new(sink));
+    }
+
+    fn log(&mut self, event: BusEvent) {
+        if let Some(sink) = self.log_sink.as_mut() {
+            sink(event);
         }
-        self.rom.prg_rom[addr as usize]
     }
-}
\ No newline at end of file
+
+    // controller in port 0 ($4016) or 1 ($4017)
+    pub fn joypad(&mut self, port: usize) -> &mut Joypad {
+        &mut self.joypads[port]
+    }
+
+    pub fn oam(&self) -> &[u8; 256] {
+        &self.oam_data
+    }
+
+    pub fn mapper(&self) -> &dyn Mapper {
+        self.mapper.as_ref()
+    }
+
+    pub fn mapper_mut(&mut self) -> &mut dyn Mapper {
+        self.mapper.as_mut()
+    }
+}
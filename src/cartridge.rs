@@ -1,30 +1,164 @@
+use crate::error::{EmulatorError, InvalidRomKind};
+
 const NES_TAG: [u8; 4] = [0x4E, 0x45, 0x53, 0x1A];
+const HEADER_SIZE: usize = 16;
+const TRAINER_SIZE: usize = 512;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
 pub enum Mirroring {
     VERTICAL,
     HORIZONTAL,
     FOUR_SCREEN,
+    SINGLE_SCREEN_LOWER, // every nametable is the first 1 KiB of VRAM, selected by the mapper
+    SINGLE_SCREEN_UPPER, // every nametable is the second 1 KiB
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HeaderFormat {
+    #[default]
+    INes,
+    Nes2,
+}
+
+// the CPU/PPU timing the game was made for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Timing {
+    #[default]
+    Ntsc,
+    Pal,
+    MultiRegion,
+    Dendy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConsoleType {
+    #[default]
+    Nes, // or Famicom
+    VsSystem,
+    Playchoice10,
+    Extended(u8), // NES 2.0 extended console type, e.g. 3 for the VT01 famiclones
+}
+
+// the arcade hardware a Vs. System game expects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VsSystem {
+    pub ppu: u8,      // NES 2.0 Vs. PPU type, which decides the palette and $2002 protection
+    pub hardware: u8, // NES 2.0 Vs. hardware type, e.g. 0 for the Unisystem
+}
+
+// everything the header says about the cartridge; an iNES 1.0 header only fills in the
+// mapper, sizes and timing and leaves the rest at the defaults
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RomHeader {
+    pub format: HeaderFormat,
+    pub mapper: u16,
+    pub submapper: u8,
+    pub prg_rom_size: usize,
+    pub chr_rom_size: usize,
+    pub prg_ram_size: usize,   // volatile
+    pub prg_nvram_size: usize, // battery-backed or EEPROM
+    pub chr_ram_size: usize,
+    pub chr_nvram_size: usize,
+    pub timing: Timing,
+    pub console: ConsoleType,
+    pub vs_system: Option<VsSystem>,
+    pub misc_roms: u8,
+    pub expansion_device: u8, // NES 2.0 default expansion device, 1 for the standard controllers
+}
+
+impl RomHeader {
+    fn parse_ines(raw: &[u8]) -> RomHeader {
+        let battery = raw[6] & 0b10 != 0;
+        // the PRG-RAM field was a late addition and is usually 0, which means 8 KiB
+        let prg_ram_size = raw[8].max(1) as usize * 8192;
+        let chr_rom_size = raw[5] as usize * 8192;
+        RomHeader {
+            format: HeaderFormat::INes,
+            mapper: ((raw[7] & 0b1111_0000) | (raw[6] >> 4)) as u16,
+            prg_rom_size: raw[4] as usize * 16384,
+            chr_rom_size,
+            prg_ram_size: if battery { 0 } else { prg_ram_size },
+            prg_nvram_size: if battery { prg_ram_size } else { 0 },
+            chr_ram_size: if chr_rom_size == 0 { 8192 } else { 0 },
+            timing: if raw[9] & 1 != 0 { Timing::Pal } else { Timing::Ntsc },
+            ..RomHeader::default()
+        }
+    }
+
+    fn parse_nes2(raw: &[u8]) -> RomHeader {
+        let console = match raw[7] & 0b11 {
+            0 => ConsoleType::Nes,
+            1 => ConsoleType::VsSystem,
+            2 => ConsoleType::Playchoice10,
+            _ => ConsoleType::Extended(raw[13] & 0x0F),
+        };
+        RomHeader {
+            format: HeaderFormat::Nes2,
+            mapper: ((raw[8] & 0x0F) as u16) << 8 | (raw[7] & 0b1111_0000) as u16 | (raw[6] >> 4) as u16,
+            submapper: raw[8] >> 4,
+            prg_rom_size: nes2_rom_size(raw[4], raw[9] & 0x0F, 16384),
+            chr_rom_size: nes2_rom_size(raw[5], raw[9] >> 4, 8192),
+            prg_ram_size: nes2_ram_size(raw[10] & 0x0F),
+            prg_nvram_size: nes2_ram_size(raw[10] >> 4),
+            chr_ram_size: nes2_ram_size(raw[11] & 0x0F),
+            chr_nvram_size: nes2_ram_size(raw[11] >> 4),
+            timing: match raw[12] & 0b11 {
+                0 => Timing::Ntsc,
+                1 => Timing::Pal,
+                2 => Timing::MultiRegion,
+                _ => Timing::Dendy,
+            },
+            console,
+            vs_system: match console {
+                ConsoleType::VsSystem => Some(VsSystem { ppu: raw[13] & 0x0F, hardware: raw[13] >> 4 }),
+                _ => None,
+            },
+            misc_roms: raw[14] & 0b11,
+            expansion_device: raw[15] & 0b0011_1111,
+        }
+    }
+}
+
+// a size in `unit`s with a 4-bit high byte, or when that is $F, 2^E * (MM * 2 + 1) bytes
+// from the EEEEEEMM low byte
+fn nes2_rom_size(low: u8, high: u8, unit: usize) -> usize {
+    if high == 0x0F {
+        let exponent = (low >> 2) as u32;
+        let multiplier = (low & 0b11) as usize * 2 + 1;
+        2usize.saturating_pow(exponent).saturating_mul(multiplier)
+    } else {
+        ((high as usize) << 8 | low as usize) * unit
+    }
+}
+
+// RAM sizes are shift counts: 0 for none, otherwise 64 << shift bytes
+fn nes2_ram_size(shift: u8) -> usize {
+    if shift == 0 { 0 } else { 64 << shift }
 }
 
 pub struct Rom {
     pub prg_rom: Vec<u8>,
     pub chr_rom: Vec<u8>,
-    pub mapper: u8,
+    pub mapper: u16,
     pub screen_mirroring: Mirroring,
+    pub battery: bool, // battery-backed PRG-RAM (or other save memory)
+    pub header: RomHeader,
 }
 
 impl Rom {
-    pub fn new(raw: &Vec<u8>) -> Result<Rom, String> {
-        if (&raw[0..4] != NES_TAG) { // check header
-            return Err("Invalid NES file".to_string());
+    pub fn new(raw: &[u8]) -> Result<Rom, EmulatorError> {
+        if raw.len() < HEADER_SIZE {
+            return Err(EmulatorError::TruncatedRom { expected: HEADER_SIZE, actual: raw.len() });
         }
-        let mapper = (raw[7] & 0b1111_0000) | (raw[6] >> 4);
-
-        let ines_ver = (raw[7] >> 2) & 0b11;
-        if ines_ver != 0 { // check version
-            return Err("This NES format is not supported".to_string());
+        if raw[0..4] != NES_TAG { // check header
+            return Err(EmulatorError::InvalidRom(InvalidRomKind::MissingNesTag));
         }
+        let header = match (raw[7] >> 2) & 0b11 { // check version
+            0 => RomHeader::parse_ines(raw),
+            2 => RomHeader::parse_nes2(raw),
+            _ => return Err(EmulatorError::InvalidRom(InvalidRomKind::UnsupportedFormat)),
+        };
 
         let four_screen = raw[6] & 0b1000 != 0;
         let vertical_mirroring = raw[6] &0b1 != 0;
@@ -34,18 +168,27 @@ impl Rom {
             (false, false) => Mirroring::HORIZONTAL,
         };
 
-        let prg_rom_size = raw[4] as usize * 16384;
-        let chr_rom_size = raw[5] as usize * 8192;
+        let prg_rom_size = header.prg_rom_size;
+        let chr_rom_size = header.chr_rom_size;
 
+        let battery = raw[6] & 0b10 != 0;
         let skip_trainer = raw[6] & 0b100 != 0;
 
-        let prg_rom_start = 16 + if skip_trainer { 512 } else { 0 };
-        let chr_rom_start = prg_rom_start * 2;
+        let prg_rom_start = HEADER_SIZE + if skip_trainer { TRAINER_SIZE } else { 0 };
+        let chr_rom_start = prg_rom_start.saturating_add(prg_rom_size);
+
+        let expected = chr_rom_start.saturating_add(chr_rom_size);
+        if raw.len() < expected {
+            return Err(EmulatorError::TruncatedRom { expected, actual: raw.len() });
+        }
 
         Ok(Rom {
             prg_rom: raw[prg_rom_start..(prg_rom_start + prg_rom_size)].to_vec(),
             chr_rom: raw[chr_rom_start..(chr_rom_start + chr_rom_size)].to_vec(),
-            mapper,
+            mapper: header.mapper,
             screen_mirroring,
+            battery,
+            header,
         })
-    }}
+    }
+}
@@ -0,0 +1,266 @@
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::cpu::{Memory, CPU};
+
+// runner for the SingleStepTests (ProcessorTests) JSON vectors, one file per opcode ("a9.json"),
+// each holding cases like:
+// { "name": "a9 3c 1e", "initial": { "pc", "s", "a", "x", "y", "p", "ram": [[addr, value], ...] },
+//   "final": { ...same... }, "cycles": [[addr, value, "read" | "write"], ...] }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BusAccess {
+    Read,
+    Write,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BusCycle {
+    pub address: u16,
+    pub value: u8,
+    pub access: BusAccess,
+}
+
+// flat 64 KiB memory that logs every read and write the CPU makes
+pub struct RecordingMemory {
+    data: Box<[u8; 0x10000]>,
+    pub log: Vec<BusCycle>,
+}
+
+impl RecordingMemory {
+    pub fn new() -> Self {
+        RecordingMemory {
+            data: Box::new([0; 0x10000]),
+            log: Vec::new(),
+        }
+    }
+}
+
+impl Default for RecordingMemory {
+    fn default() -> Self {
+        RecordingMemory::new()
+    }
+}
+
+impl Memory for RecordingMemory {
+    fn mem_read(&mut self, address: u16) -> u8 {
+        let value = self.data[address as usize];
+        self.log.push(BusCycle { address, value, access: BusAccess::Read });
+        value
+    }
+
+    fn mem_write(&mut self, address: u16, value: u8) {
+        self.data[address as usize] = value;
+        self.log.push(BusCycle { address, value, access: BusAccess::Write });
+    }
+
+    fn peek(&self, address: u16) -> u8 {
+        self.data[address as usize]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpuState {
+    pub pc: u16,
+    pub sp: u8,
+    pub acc: u8,
+    pub index_x: u8,
+    pub index_y: u8,
+    pub status: u8,
+    pub ram: Vec<(u16, u8)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TestCase {
+    pub name: String,
+    pub initial: CpuState,
+    pub expected: CpuState,
+    pub cycles: Vec<BusCycle>,
+}
+
+// runs one case, returning a description of the first mismatch
+pub fn run_case(case: &TestCase, check_cycles: bool) -> Result<(), String> {
+    let mut memory = RecordingMemory::new();
+    for (address, value) in &case.initial.ram {
+        memory.data[*address as usize] = *value;
+    }
+
+    let mut cpu = CPU::new(memory);
+    cpu.pc = case.initial.pc;
+    cpu.sp = case.initial.sp;
+    cpu.acc = case.initial.acc;
+    cpu.index_x = case.initial.index_x;
+    cpu.index_y = case.initial.index_y;
+    cpu.status = case.initial.status;
+
+    cpu.step().map_err(|err| err.to_string())?;
+
+    let expected = &case.expected;
+    let registers = [
+        ("pc", cpu.pc, expected.pc),
+        ("s", cpu.sp as u16, expected.sp as u16),
+        ("a", cpu.acc as u16, expected.acc as u16),
+        ("x", cpu.index_x as u16, expected.index_x as u16),
+        ("y", cpu.index_y as u16, expected.index_y as u16),
+        ("p", cpu.status as u16, expected.status as u16),
+    ];
+    for (name, actual, wanted) in registers {
+        if actual != wanted {
+            return Err(format!("{}: got ${:02X}, expected ${:02X}", name, actual, wanted));
+        }
+    }
+
+    for (address, wanted) in &expected.ram {
+        let actual = cpu.peek(*address);
+        if actual != *wanted {
+            return Err(format!("ram ${:04X}: got ${:02X}, expected ${:02X}", address, actual, wanted));
+        }
+    }
+
+    if check_cycles && cpu.bus.log != case.cycles {
+        return Err(format!(
+            "bus cycles: got {} {:?}, expected {} {:?}",
+            cpu.bus.log.len(),
+            cpu.bus.log,
+            case.cycles.len(),
+            case.cycles
+        ));
+    }
+    Ok(())
+}
+
+fn field(value: &Value, name: &str) -> Result<u64, String> {
+    value[name].as_u64().ok_or_else(|| format!("missing field '{}'", name))
+}
+
+fn parse_state(value: &Value) -> Result<CpuState, String> {
+    let ram = value["ram"]
+        .as_array()
+        .ok_or("missing field 'ram'")?
+        .iter()
+        .map(|entry| match (entry[0].as_u64(), entry[1].as_u64()) {
+            (Some(address), Some(value)) => Ok((address as u16, value as u8)),
+            _ => Err(format!("bad ram entry {}", entry)),
+        })
+        .collect::<Result<Vec<(u16, u8)>, String>>()?;
+
+    Ok(CpuState {
+        pc: field(value, "pc")? as u16,
+        sp: field(value, "s")? as u8,
+        acc: field(value, "a")? as u8,
+        index_x: field(value, "x")? as u8,
+        index_y: field(value, "y")? as u8,
+        status: field(value, "p")? as u8,
+        ram,
+    })
+}
+
+fn parse_cycle(value: &Value) -> Result<BusCycle, String> {
+    let access = match value[2].as_str() {
+        Some("read") => BusAccess::Read,
+        Some("write") => BusAccess::Write,
+        _ => return Err(format!("bad cycle {}", value)),
+    };
+    match (value[0].as_u64(), value[1].as_u64()) {
+        (Some(address), Some(data)) => Ok(BusCycle {
+            address: address as u16,
+            value: data as u8,
+            access,
+        }),
+        _ => Err(format!("bad cycle {}", value)),
+    }
+}
+
+pub fn parse_cases(json: &str) -> Result<Vec<TestCase>, String> {
+    let root: Value = serde_json::from_str(json).map_err(|err| err.to_string())?;
+    root.as_array()
+        .ok_or("expected a list of test cases")?
+        .iter()
+        .map(|case| {
+            let cycles = match case["cycles"].as_array() {
+                Some(cycles) => cycles.iter().map(parse_cycle).collect::<Result<Vec<BusCycle>, String>>()?,
+                None => Vec::new(),
+            };
+            Ok(TestCase {
+                name: case["name"].as_str().unwrap_or("").to_string(),
+                initial: parse_state(&case["initial"])?,
+                expected: parse_state(&case["final"])?,
+                cycles,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OpcodeResult {
+    pub passed: usize,
+    pub failed: usize,
+    pub first_failure: Option<String>, // "case name: mismatch"
+}
+
+pub fn run_cases(cases: &[TestCase], check_cycles: bool) -> OpcodeResult {
+    let mut result = OpcodeResult::default();
+    for case in cases {
+        match run_case(case, check_cycles) {
+            Ok(()) => result.passed += 1,
+            Err(mismatch) => {
+                result.failed += 1;
+                if result.first_failure.is_none() {
+                    result.first_failure = Some(format!("{}: {}", case.name, mismatch));
+                }
+            }
+        }
+    }
+    result
+}
+
+// runs every "xx.json" in `dir`, indexed by opcode; opcodes without a file are None
+pub fn run_directory(dir: &Path, check_cycles: bool) -> Result<Vec<Option<OpcodeResult>>, String> {
+    let mut results = vec![None; 256];
+    for (opcode, result) in results.iter_mut().enumerate() {
+        let path = dir.join(format!("{:02x}.json", opcode));
+        if !path.exists() {
+            continue;
+        }
+        let json = fs::read_to_string(&path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        let cases = parse_cases(&json).map_err(|err| format!("{}: {}", path.display(), err))?;
+        *result = Some(run_cases(&cases, check_cycles));
+    }
+    Ok(results)
+}
+
+// 16x16 grid of opcodes: '.' all passed, 'X' some failed, ' ' no tests
+//
+//    0 1 2 3 4 5 6 7 8 9 A B C D E F
+// 0x . . X . . . . . . . . . . . . .
+pub fn format_matrix(results: &[Option<OpcodeResult>]) -> String {
+    let mut out = String::from("   0 1 2 3 4 5 6 7 8 9 A B C D E F\n");
+    for row in 0..16 {
+        out.push_str(&format!("{:X}x", row));
+        for col in 0..16 {
+            let mark = match &results[row * 16 + col] {
+                Some(result) if result.failed == 0 => '.',
+                Some(_) => 'X',
+                None => ' ',
+            };
+            out.push(' ');
+            out.push(mark);
+        }
+        out.push('\n');
+    }
+
+    for (opcode, result) in results.iter().enumerate() {
+        if let Some(OpcodeResult { passed, failed, first_failure: Some(failure) }) = result {
+            out.push_str(&format!(
+                "{:02X}: {}/{} failed, first: {}\n",
+                opcode,
+                failed,
+                passed + failed,
+                failure
+            ));
+        }
+    }
+    out
+}
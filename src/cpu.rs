@@ -1,13 +1,13 @@
-use std::collections::HashMap;
 use crate::bus::Bus;
-use crate::opcodes;
+use crate::error::EmulatorError;
+use crate::opcodes::{self, Instruction};
 
 #[allow(non_snake_case)]
 pub mod StatusFlags {
     pub const CARRY: u8 = 0b0000_0001;
     pub const ZERO: u8 = 0b0000_0010;
     pub const INTERRUPT_DISABLE: u8 = 0b0000_0100;
-    pub const DECIMAL_MODE: u8 = 0b0000_1000; // not used in NES
+    pub const DECIMAL_MODE: u8 = 0b0000_1000; // ignored by the NES's 2A03
     pub const BREAK: u8 = 0b0001_0000;
     pub const BREAK2: u8 = 0b0010_0000;
     pub const OVERFLOW: u8 = 0b0100_0000;
@@ -17,17 +17,73 @@ pub mod StatusFlags {
 const STACK: u16 = 0x0100;
 const STACK_RESET: u8 = 0xfd;
 
-pub struct CPU {
+// the "magic" constant mixed into the unstable XAA and LAX #imm results
+const UNSTABLE_MAGIC: u8 = 0xEE;
+
+fn page_crossed(a: u16, b: u16) -> bool {
+    (a & 0xFF00) != (b & 0xFF00)
+}
+
+// the address an indexed access reads before the carry reaches the high byte
+fn uncorrected_address(addr: u16, page_cross: bool) -> u16 {
+    if page_cross {
+        addr.wrapping_sub(0x100)
+    } else {
+        addr
+    }
+}
+
+// which member of the 6502 family to behave as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CpuVariant {
+    #[default]
+    Nes2A03, // NMOS core with decimal mode cut out
+    Nmos6502, // original NMOS part, BCD arithmetic with its N/V/Z quirks
+    Cmos65C02, // WDC 65C02: new opcodes, JMP ($xxFF) fixed, valid BCD flags
+}
+
+// the 6502 core, generic over whatever memory it is wired to (the NES bus by default)
+pub struct CPU<M: Memory = Bus> {
     pub acc: u8,
     pub status: u8,
     pub index_x: u8,
     pub index_y: u8,
     pub sp: u8, // stack pointer
     pub pc: u16, // program counter
-    pub bus: Bus,
+    pub cycles: u64, // total cycles run since power on, one per bus access
+    nmi_line: bool, // nmi input driven through set_nmi_line
+    bus_nmi_line: bool, // nmi level last seen from the bus, for edge detection
+    nmi_pending: bool,
+    irq_line: bool, // irq input driven through set_irq_line
+    polled_interrupt_disable: bool, // I flag as seen by the last interrupt poll
+    waiting: bool, // 65C02 WAI, idling until an interrupt
+    jumped: bool, // the instruction being run has set pc itself
+    variant: CpuVariant,
+    pub bus: M,
 }
 
-#[derive(Debug)]
+const NMI_VECTOR: u16 = 0xFFFA;
+const RESET_VECTOR: u16 = 0xFFFC;
+const IRQ_VECTOR: u16 = 0xFFFE; // shared with BRK
+
+// interrupts the cpu can service, hardware or software
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interrupt {
+    Break,
+    Nmi,
+    Irq,
+}
+
+// what a single call to CPU::step did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepResult {
+    pub opcode: u8,
+    pub cycles: u16, // including any OAM DMA the instruction started
+    pub pc: u16, // program counter after the instruction
+    pub interrupt: Option<Interrupt>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
 pub enum AddressingMode {
     Immediate,
@@ -39,28 +95,60 @@ pub enum AddressingMode {
     AbsoluteY,
     IndirectX,
     IndirectY,
-    NonAddressing,
+    Indirect, // JMP ($xxxx) only
+    Relative, // branches
+    ZeroPageIndirect, // 65C02 ($zz)
+    AbsoluteIndexedIndirect, // 65C02 JMP ($xxxx,X)
+    ZeroPageRelative, // 65C02 BBR/BBS $zz,target
+    Accumulator,
+    NonAddressing, // implied
 }
 
 pub trait Memory {
-    fn mem_read(&self, address: u16) -> u8;
+    fn mem_read(&mut self, address: u16) -> u8;
     fn mem_write(&mut self, address: u16, value: u8);
 
-    fn mem_read_u16(&self, pos: u16) -> u16 {
+    // reads without side effects on I/O registers, for tracing and debugging
+    fn peek(&self, address: u16) -> u8;
+
+    fn mem_read_u16(&mut self, pos: u16) -> u16 {
         let lo = self.mem_read(pos) as u16;
-        let hi = self.mem_read(pos + 1) as u16;
+        let hi = self.mem_read(pos.wrapping_add(1)) as u16;
+        (hi << 8) | (lo)
+    }
+    fn peek_u16(&self, pos: u16) -> u16 {
+        let lo = self.peek(pos) as u16;
+        let hi = self.peek(pos.wrapping_add(1)) as u16;
         (hi << 8) | (lo)
     }
     fn mem_write_u16(&mut self, pos: u16, data: u16) {
         let hi = (data >> 8) as u8;
         let lo = (data & 0xFF) as u8;
         self.mem_write(pos, lo);
-        self.mem_write(pos + 1, hi);
+        self.mem_write(pos.wrapping_add(1), hi);
+    }
+
+    // called by the cpu once per cycle, right after that cycle's read or write,
+    // so other chips on the bus can be advanced in lockstep
+    fn tick(&mut self) {}
+
+    // interrupt lines driven by devices on the bus (PPU, APU, mappers)
+    fn nmi_line(&self) -> bool {
+        false
+    }
+    fn irq_line(&self) -> bool {
+        false
+    }
+
+    // the page of a pending OAM DMA (a write to $4014), which the cpu services by halting
+    // while it copies 256 bytes from that page to $2004
+    fn take_oam_dma(&mut self) -> Option<u8> {
+        None
     }
 }
 
-impl Memory for CPU {
-    fn mem_read(&self, address: u16) -> u8 {
+impl<M: Memory> Memory for CPU<M> {
+    fn mem_read(&mut self, address: u16) -> u8 {
         self.bus.mem_read(address)
     }
 
@@ -68,7 +156,11 @@ impl Memory for CPU {
         self.bus.mem_write(address, value);
     }
 
-    fn mem_read_u16(&self, pos: u16) -> u16 {
+    fn peek(&self, address: u16) -> u8 {
+        self.bus.peek(address)
+    }
+
+    fn mem_read_u16(&mut self, pos: u16) -> u16 {
         self.bus.mem_read_u16(pos)
     }
 
@@ -77,8 +169,12 @@ impl Memory for CPU {
     }
 }
 
-impl CPU {
-    pub fn new(bus: Bus) -> CPU {
+impl<M: Memory> CPU<M> {
+    pub fn new(bus: M) -> CPU<M> {
+        CPU::with_variant(bus, CpuVariant::default())
+    }
+
+    pub fn with_variant(bus: M, variant: CpuVariant) -> CPU<M> {
         CPU {
             acc: 0,
             status: StatusFlags::INTERRUPT_DISABLE | StatusFlags::BREAK2,
@@ -86,15 +182,49 @@ impl CPU {
             index_y: 0,
             sp: STACK_RESET,
             pc: 0,
+            cycles: 0,
+            nmi_line: false,
+            bus_nmi_line: false,
+            nmi_pending: false,
+            irq_line: false,
+            polled_interrupt_disable: true,
+            jumped: false,
+            waiting: false,
+            variant,
             bus,
         }
     }
 
+    pub fn variant(&self) -> CpuVariant {
+        self.variant
+    }
+
+    // one cpu cycle: a single bus read, after which the rest of the bus is ticked
+    fn read(&mut self, address: u16) -> u8 {
+        let value = self.bus.mem_read(address);
+        self.cycles += 1;
+        self.bus.tick();
+        value
+    }
+
+    // one cpu cycle: a single bus write
+    fn write(&mut self, address: u16, value: u8) {
+        self.bus.mem_write(address, value);
+        self.cycles += 1;
+        self.bus.tick();
+    }
+
+    fn read_u16(&mut self, pos: u16) -> u16 {
+        let lo = self.read(pos) as u16;
+        let hi = self.read(pos.wrapping_add(1)) as u16;
+        (hi << 8) | lo
+    }
+
     fn set_flag(&mut self, flag: u8, value: bool) {
         if value {
-            self.status = self.status | flag;
+            self.status |= flag;
         } else {
-            self.status = self.status & !flag;
+            self.status &= !flag;
         }
     }
 
@@ -103,38 +233,35 @@ impl CPU {
     }
 
     fn lda(&mut self, mode:&AddressingMode) {
-        let addr = self.get_operand_address(&mode);
-        let value = self.mem_read(addr);
+        let value = self.read_operand(mode);
 
         self.acc = value;
         self.update_zero_and_negative_flags(self.acc);
     }
 
     fn ldx(&mut self, mode:&AddressingMode) {
-        let addr = self.get_operand_address(&mode);
-        self.index_x = self.mem_read(addr);
+        self.index_x = self.read_operand(mode);
         self.update_zero_and_negative_flags(self.index_x);
     }
 
     fn ldy(&mut self, mode:&AddressingMode) {
-        let addr = self.get_operand_address(&mode);
-        self.index_y = self.mem_read(addr);
+        self.index_y = self.read_operand(mode);
         self.update_zero_and_negative_flags(self.index_y);
     }
 
     fn sta(&mut self, mode:&AddressingMode) {
-        let addr = self.get_operand_address(&mode);
-        self.mem_write(addr, self.acc);
+        let addr = self.write_address(mode);
+        self.write(addr, self.acc);
     }
 
     fn stx(&mut self, mode:&AddressingMode) {
-        let addr = self.get_operand_address(&mode);
-        self.mem_write(addr, self.index_x);
+        let addr = self.write_address(mode);
+        self.write(addr, self.index_x);
     }
 
     fn sty(&mut self, mode:&AddressingMode) {
-        let addr = self.get_operand_address(&mode);
-        self.mem_write(addr, self.index_y);
+        let addr = self.write_address(mode);
+        self.write(addr, self.index_y);
     }
 
     fn tax(&mut self) {
@@ -167,10 +294,7 @@ impl CPU {
     }
 
     fn inc(&mut self, mode: &AddressingMode) {
-        let addr = self.get_operand_address(mode);
-        let value = self.mem_read(addr);
-        let result = value.wrapping_add(1);
-        self.mem_write(addr, result);
+        let result = self.modify_memory(mode, |_, value| value.wrapping_add(1));
         self.update_zero_and_negative_flags(result);
     }
 
@@ -186,11 +310,8 @@ impl CPU {
     }
 
     fn dec(&mut self, mode: &AddressingMode) {
-        let addr = self.get_operand_address(mode);
-        let mut value = self.mem_read(addr);
-        value = value.wrapping_sub(1);
-        self.mem_write(addr, value);
-        self.update_zero_and_negative_flags(value);
+        let result = self.modify_memory(mode, |_, value| value.wrapping_sub(1));
+        self.update_zero_and_negative_flags(result);
     }
 
     fn dex(&mut self) {
@@ -205,8 +326,31 @@ impl CPU {
 
     // addition with carry
     fn adc(&mut self, mode: &AddressingMode) {
-        let addr = self.get_operand_address(mode);
-        let value = self.mem_read(addr);
+        let value = self.read_operand(mode);
+        self.add_to_acc(value);
+        self.decimal_fixup_cycle();
+    }
+
+    fn decimal_enabled(&self) -> bool {
+        self.variant != CpuVariant::Nes2A03 && self.get_flag(StatusFlags::DECIMAL_MODE)
+    }
+
+    // the 65C02 spends an extra cycle correcting the flags of a decimal ADC/SBC
+    fn decimal_fixup_cycle(&mut self) {
+        if self.variant == CpuVariant::Cmos65C02 && self.get_flag(StatusFlags::DECIMAL_MODE) {
+            self.read(self.pc);
+        }
+    }
+
+    fn add_to_acc(&mut self, value: u8) {
+        if self.decimal_enabled() {
+            self.add_decimal(value);
+        } else {
+            self.add_binary(value);
+        }
+    }
+
+    fn add_binary(&mut self, value: u8) {
         let carry_in = self.get_flag(StatusFlags::CARRY) as u8;
 
         let sum = self.acc as u16 + value as u16 + carry_in as u16;
@@ -226,52 +370,111 @@ impl CPU {
 
     // subtract and carry
     fn sbc(&mut self, mode: &AddressingMode) {
-        let addr = self.get_operand_address(mode);
-        let value = self.mem_read(addr);
+        let value = self.read_operand(mode);
+        self.sub_from_acc(value);
+        self.decimal_fixup_cycle();
+    }
+
+    fn sub_from_acc(&mut self, value: u8) {
+        if self.decimal_enabled() {
+            self.sub_decimal(value);
+        } else {
+            self.sub_binary(value);
+        }
+    }
 
-        // Perform subtraction using 16-bit arithmetic to handle borrow
+    fn sub_binary(&mut self, value: u8) {
         // A - M - (1 - C) is the same as A + (-M-1) + C, where -M-1 is the two's complement ~M.
+        // The carry flag then acts as a "not borrow" flag
         let operand = (value as i8).wrapping_neg().wrapping_sub(1) as u8;
-        let carry_in = self.get_flag(StatusFlags::CARRY) as u8;
+        self.add_binary(operand);
+    }
 
-        let sum = self.acc as u16 + operand as u16 + carry_in as u16;
+    // BCD addition, digit by digit (after Bruce Clark's "Decimal Mode" appendix A)
+    // on NMOS parts N and V come from the sum before the high digit is corrected and Z from
+    // the binary sum; the 65C02 sets N and Z from the result
+    fn add_decimal(&mut self, value: u8) {
+        let carry_in = self.get_flag(StatusFlags::CARRY) as u16;
+        let binary = self.acc.wrapping_add(value).wrapping_add(carry_in as u8);
 
-        // Set carry flag (which is a "not borrow" flag)
-        self.set_flag(StatusFlags::CARRY, sum > 0xFF);
-        let result = sum as u8;
+        let mut lo = (self.acc & 0x0F) as u16 + (value & 0x0F) as u16 + carry_in;
+        if lo >= 0x0A {
+            lo = ((lo + 0x06) & 0x0F) + 0x10;
+        }
+        let mut sum = (self.acc & 0xF0) as u16 + (value & 0xF0) as u16 + lo;
+        let signed = (self.acc & 0xF0) as i8 as i16 + (value & 0xF0) as i8 as i16 + lo as i16;
+        let uncorrected = sum as u8;
+        if sum >= 0xA0 {
+            sum += 0x60;
+        }
 
-        // Set overflow flag
-        let overflow = (self.acc ^ result) & (operand ^ result) & 0x80 != 0;
-        self.set_flag(StatusFlags::OVERFLOW, overflow);
+        self.set_flag(StatusFlags::CARRY, sum >= 0x100);
+        self.set_flag(StatusFlags::OVERFLOW, !(-128..=127).contains(&signed));
+        self.acc = sum as u8;
+        if self.variant == CpuVariant::Cmos65C02 {
+            self.update_zero_and_negative_flags(self.acc);
+        } else {
+            self.set_flag(StatusFlags::ZERO, binary == 0);
+            self.set_flag(StatusFlags::NEGATIVE, uncorrected & 0x80 != 0);
+        }
+    }
 
-        self.acc = result;
-        self.update_zero_and_negative_flags(self.acc);
+    // BCD subtraction; C, V and (on NMOS) N and Z are those of the binary subtraction
+    fn sub_decimal(&mut self, value: u8) {
+        let acc = self.acc as i16;
+        let operand = value as i16;
+        let borrow = 1 - self.get_flag(StatusFlags::CARRY) as i16;
+        self.sub_binary(value);
+
+        let lo = (acc & 0x0F) - (operand & 0x0F) - borrow;
+        let result = if self.variant == CpuVariant::Cmos65C02 {
+            let mut result = acc - operand - borrow;
+            if result < 0 {
+                result -= 0x60;
+            }
+            if lo < 0 {
+                result -= 0x06;
+            }
+            result
+        } else {
+            let lo = if lo < 0 { ((lo - 0x06) & 0x0F) - 0x10 } else { lo };
+            let mut result = (acc & 0xF0) - (operand & 0xF0) + lo;
+            if result < 0 {
+                result -= 0x60;
+            }
+            result
+        };
+
+        self.acc = result as u8;
+        if self.variant == CpuVariant::Cmos65C02 {
+            self.update_zero_and_negative_flags(self.acc);
+        }
     }
 
     fn and(&mut self, mode: &AddressingMode) {
-        let addr = self.get_operand_address(mode);
-        let value = self.mem_read(addr);
-        self.acc = self.acc & value;
+        let value = self.read_operand(mode);
+        self.acc &= value;
         self.update_zero_and_negative_flags(self.acc);
     }
 
     fn eor(&mut self, mode: &AddressingMode) {
-        let addr = self.get_operand_address(mode);
-        let value = self.mem_read(addr);
-        self.acc = self.acc ^ value;
+        let value = self.read_operand(mode);
+        self.acc ^= value;
         self.update_zero_and_negative_flags(self.acc);
     }
 
     fn ora(&mut self, mode: &AddressingMode) {
-        let addr = self.get_operand_address(mode);
-        let value = self.mem_read(addr);
-        self.acc = self.acc | value;
+        let value = self.read_operand(mode);
+        self.acc |= value;
         self.update_zero_and_negative_flags(self.acc);
     }
 
     fn compare(&mut self, mode: &AddressingMode, reg_value: u8) {
-        let addr = self.get_operand_address(mode);
-        let value = self.mem_read(addr);
+        let value = self.read_operand(mode);
+        self.compare_value(reg_value, value);
+    }
+
+    fn compare_value(&mut self, reg_value: u8, value: u8) {
         let result = reg_value.wrapping_sub(value);
 
         self.set_flag(StatusFlags::CARRY, reg_value >= value);
@@ -279,14 +482,189 @@ impl CPU {
     }
 
     fn bit(&mut self, mode: &AddressingMode) {
-        let addr = self.get_operand_address(mode);
-        let value = self.mem_read(addr);
+        let value = self.read_operand(mode);
 
         self.set_flag(StatusFlags::ZERO, (self.acc & value) == 0);
         self.set_flag(StatusFlags::NEGATIVE, (value & StatusFlags::NEGATIVE) != 0);
         self.set_flag(StatusFlags::OVERFLOW, (value & StatusFlags::OVERFLOW) != 0);
     }
 
+    // --- Shifts and Rotates ---
+    fn asl_value(&mut self, value: u8) -> u8 {
+        self.set_flag(StatusFlags::CARRY, (value & 0x80) > 0);
+        let result = value << 1;
+        self.update_zero_and_negative_flags(result);
+        result
+    }
+
+    fn lsr_value(&mut self, value: u8) -> u8 {
+        self.set_flag(StatusFlags::CARRY, (value & 0x01) > 0);
+        let result = value >> 1;
+        self.update_zero_and_negative_flags(result);
+        result
+    }
+
+    fn rol_value(&mut self, value: u8) -> u8 {
+        let old_carry = self.get_flag(StatusFlags::CARRY);
+        self.set_flag(StatusFlags::CARRY, (value & 0x80) > 0);
+        let mut result = value << 1;
+        if old_carry {
+            result |= 0x01;
+        }
+        self.update_zero_and_negative_flags(result);
+        result
+    }
+
+    fn ror_value(&mut self, value: u8) -> u8 {
+        let old_carry = self.get_flag(StatusFlags::CARRY);
+        self.set_flag(StatusFlags::CARRY, (value & 0x01) > 0);
+        let mut result = value >> 1;
+        if old_carry {
+            result |= 0x80;
+        }
+        self.update_zero_and_negative_flags(result);
+        result
+    }
+
+    // applies a read-modify-write operation to memory, returning the written value
+    fn modify_memory<F>(&mut self, mode: &AddressingMode, op: F) -> u8
+    where
+        F: FnOnce(&mut Self, u8) -> u8,
+    {
+        let addr = self.write_address(mode);
+        self.modify_at(addr, op)
+    }
+
+    // while the ALU works, NMOS parts write the unmodified value back and the 65C02 reads it again
+    fn modify_at<F>(&mut self, addr: u16, op: F) -> u8
+    where
+        F: FnOnce(&mut Self, u8) -> u8,
+    {
+        let value = self.read(addr);
+        if self.variant == CpuVariant::Cmos65C02 {
+            self.read(addr);
+        } else {
+            self.write(addr, value);
+        }
+        let result = op(self, value);
+        self.write(addr, result);
+        result
+    }
+
+    // --- Unofficial Opcodes ---
+    fn lax(&mut self, mode: &AddressingMode) {
+        let value = self.read_operand(mode);
+        self.acc = value;
+        self.index_x = value;
+        self.update_zero_and_negative_flags(value);
+    }
+
+    fn sax(&mut self, mode: &AddressingMode) {
+        let addr = self.write_address(mode);
+        self.write(addr, self.acc & self.index_x);
+    }
+
+    fn dcp(&mut self, mode: &AddressingMode) {
+        let value = self.modify_memory(mode, |_, value| value.wrapping_sub(1));
+        self.compare_value(self.acc, value);
+    }
+
+    fn isb(&mut self, mode: &AddressingMode) {
+        let value = self.modify_memory(mode, |_, value| value.wrapping_add(1));
+        self.sub_from_acc(value);
+    }
+
+    fn slo(&mut self, mode: &AddressingMode) {
+        let value = self.modify_memory(mode, Self::asl_value);
+        self.acc |= value;
+        self.update_zero_and_negative_flags(self.acc);
+    }
+
+    fn rla(&mut self, mode: &AddressingMode) {
+        let value = self.modify_memory(mode, Self::rol_value);
+        self.acc &= value;
+        self.update_zero_and_negative_flags(self.acc);
+    }
+
+    fn sre(&mut self, mode: &AddressingMode) {
+        let value = self.modify_memory(mode, Self::lsr_value);
+        self.acc ^= value;
+        self.update_zero_and_negative_flags(self.acc);
+    }
+
+    fn rra(&mut self, mode: &AddressingMode) {
+        let value = self.modify_memory(mode, Self::ror_value);
+        self.add_to_acc(value);
+    }
+
+    fn anc(&mut self, mode: &AddressingMode) {
+        self.acc &= self.read_operand(mode);
+        self.update_zero_and_negative_flags(self.acc);
+        self.set_flag(StatusFlags::CARRY, self.get_flag(StatusFlags::NEGATIVE));
+    }
+
+    fn alr(&mut self, mode: &AddressingMode) {
+        let value = self.acc & self.read_operand(mode);
+        self.acc = self.lsr_value(value);
+    }
+
+    fn arr(&mut self, mode: &AddressingMode) {
+        let value = self.acc & self.read_operand(mode);
+        self.acc = self.ror_value(value);
+        // carry and overflow come from bits 6 and 5 of the rotated result
+        let bit6 = (self.acc >> 6) & 1;
+        let bit5 = (self.acc >> 5) & 1;
+        self.set_flag(StatusFlags::CARRY, bit6 == 1);
+        self.set_flag(StatusFlags::OVERFLOW, (bit6 ^ bit5) == 1);
+    }
+
+    fn axs(&mut self, mode: &AddressingMode) {
+        let value = self.read_operand(mode);
+        let and = self.acc & self.index_x;
+        self.set_flag(StatusFlags::CARRY, and >= value);
+        self.index_x = and.wrapping_sub(value);
+        self.update_zero_and_negative_flags(self.index_x);
+    }
+
+    fn xaa(&mut self, mode: &AddressingMode) {
+        let value = self.read_operand(mode);
+        self.acc = (self.acc | UNSTABLE_MAGIC) & self.index_x & value;
+        self.update_zero_and_negative_flags(self.acc);
+    }
+
+    fn lxa(&mut self, mode: &AddressingMode) {
+        let value = self.read_operand(mode);
+        self.acc = (self.acc | UNSTABLE_MAGIC) & value;
+        self.index_x = self.acc;
+        self.update_zero_and_negative_flags(self.acc);
+    }
+
+    fn las(&mut self, mode: &AddressingMode) {
+        let value = self.read_operand(mode) & self.sp;
+        self.acc = value;
+        self.index_x = value;
+        self.sp = value;
+        self.update_zero_and_negative_flags(value);
+    }
+
+    // SHX/SHY/AHX/TAS store the register ANDed with the high address byte + 1
+    // when indexing crosses a page, that value also replaces the high byte of the address
+    fn store_and_high(&mut self, mode: &AddressingMode, reg_value: u8) {
+        let (addr, page_cross) = self.get_operand_address(mode);
+        self.page_fixup_read(mode, addr, page_cross);
+        let base_hi = match mode {
+            AddressingMode::AbsoluteX => (addr.wrapping_sub(self.index_x as u16) >> 8) as u8,
+            _ => (addr.wrapping_sub(self.index_y as u16) >> 8) as u8,
+        };
+        let value = reg_value & base_hi.wrapping_add(1);
+        let addr = if page_cross {
+            ((value as u16) << 8) | (addr & 0x00FF)
+        } else {
+            addr
+        };
+        self.write(addr, value);
+    }
+
     fn update_zero_and_negative_flags(&mut self, value: u8) {
         self.set_flag(StatusFlags::ZERO, value == 0);
         self.set_flag(StatusFlags::NEGATIVE, (value & 0b1000_0000) != 0);
@@ -294,13 +672,18 @@ impl CPU {
 
     // --- Stack Functionality ---
     fn stack_push(&mut self, value: u8) {
-        self.mem_write(STACK + self.sp as u16, value);
+        self.write(STACK + self.sp as u16, value);
         self.sp = self.sp.wrapping_sub(1);
     }
 
     fn stack_pop(&mut self) -> u8 {
         self.sp = self.sp.wrapping_add(1);
-        self.mem_read(STACK + self.sp as u16)
+        self.read(STACK + self.sp as u16)
+    }
+
+    // the cycle spent incrementing the stack pointer still reads the current stack slot
+    fn stack_dummy_read(&mut self) {
+        self.read(STACK + self.sp as u16);
     }
 
     fn stack_push_u16(&mut self, value: u16) {
@@ -321,6 +704,7 @@ impl CPU {
     }
 
     fn pla(&mut self) {
+        self.stack_dummy_read();
         self.acc = self.stack_pop();
         self.update_zero_and_negative_flags(self.acc);
     }
@@ -333,17 +717,35 @@ impl CPU {
     }
 
     fn plp(&mut self) {
+        self.stack_dummy_read();
         self.status = self.stack_pop();
         self.set_flag(StatusFlags::BREAK, false);
         self.set_flag(StatusFlags::BREAK2, true);
     }
 
     // all branch instructions have same logic
+    // a taken branch costs one extra cycle, or two if it lands on another page,
+    // both spent fetching from the next instruction while pc is fixed up
+    // moves pc to `target`, so step doesn't advance it past the operands afterwards
+    fn jump(&mut self, target: u16) {
+        self.pc = target;
+        self.jumped = true;
+    }
+
+    // reads the offset at pc and always leaves pc on the next instruction, taken or not
     fn branch(&mut self, condition: bool) {
+        let jump: i8 = self.read(self.pc) as i8;
+        let next = self.pc.wrapping_add(1);
         if condition {
-            let jump: i8 = self.mem_read(self.pc) as i8;
-            let jump_addr = self.pc.wrapping_add(1).wrapping_add(jump as u16);
-            self.pc = jump_addr;
+            let jump_addr = next.wrapping_add(jump as u16);
+
+            self.read(next);
+            if page_crossed(next, jump_addr) {
+                self.read((next & 0xFF00) | (jump_addr & 0x00FF));
+            }
+            self.jump(jump_addr);
+        } else {
+            self.jump(next);
         }
     }
 
@@ -354,324 +756,675 @@ impl CPU {
         self.index_y = 0;
         self.sp = STACK_RESET;
 
-        self.pc = self.mem_read_u16(0xFFFC);
+        self.pc = self.bus.mem_read_u16(RESET_VECTOR);
+        self.cycles = 7; // the reset sequence takes 7 cycles
+
+        self.nmi_pending = false;
+        self.polled_interrupt_disable = true;
+        self.waiting = false;
+    }
+
+    // --- Interrupts ---
+
+    // drives the NMI input, which is edge triggered so only a rising edge requests an NMI
+    pub fn set_nmi_line(&mut self, asserted: bool) {
+        if asserted && !self.nmi_line {
+            self.nmi_pending = true;
+        }
+        self.nmi_line = asserted;
+    }
+
+    // drives the IRQ input, which is level triggered and masked by INTERRUPT_DISABLE
+    pub fn set_irq_line(&mut self, asserted: bool) {
+        self.irq_line = asserted;
+    }
+
+    fn poll_nmi(&mut self) {
+        let level = self.bus.nmi_line();
+        if level && !self.bus_nmi_line {
+            self.nmi_pending = true;
+        }
+        self.bus_nmi_line = level;
+    }
+
+    fn irq_asserted(&self) -> bool {
+        self.irq_line || self.bus.irq_line()
+    }
+
+    // pushes the return address and status, B is only set when pushed by BRK
+    fn push_interrupt_state(&mut self, break_flag: bool) {
+        self.stack_push_u16(self.pc);
+
+        let mut flags = self.status | StatusFlags::BREAK2;
+        if break_flag {
+            flags |= StatusFlags::BREAK;
+        } else {
+            flags &= !StatusFlags::BREAK;
+        }
+        self.stack_push(flags);
+    }
+
+    // jumps through the IRQ/BRK vector, unless an NMI arrived in time to hijack it
+    fn vector_interrupt(&mut self, interrupt: Interrupt) -> Interrupt {
+        self.poll_nmi();
+        let interrupt = if self.nmi_pending {
+            self.nmi_pending = false;
+            Interrupt::Nmi
+        } else {
+            interrupt
+        };
+
+        self.set_flag(StatusFlags::INTERRUPT_DISABLE, true);
+        if self.variant == CpuVariant::Cmos65C02 {
+            self.set_flag(StatusFlags::DECIMAL_MODE, false);
+        }
+        self.polled_interrupt_disable = true;
+        self.pc = match interrupt {
+            Interrupt::Nmi => self.read_u16(NMI_VECTOR),
+            Interrupt::Break | Interrupt::Irq => self.read_u16(IRQ_VECTOR),
+        };
+        interrupt
     }
 
-    pub fn load_and_run(&mut self, program: Vec<u8>) {
+    // runs the 7 cycle hardware interrupt sequence in place of an instruction
+    fn service_interrupt(&mut self, interrupt: Interrupt) -> StepResult {
+        self.waiting = false;
+        let start = self.cycles;
+        // the opcode and operand fetches happen but are discarded, and pc is not incremented
+        self.read(self.pc);
+        self.read(self.pc);
+        self.push_interrupt_state(false);
+        let interrupt = self.vector_interrupt(interrupt);
+
+        StepResult {
+            opcode: 0x00, // the sequence is a forced BRK
+            cycles: (self.cycles - start) as u16,
+            pc: self.pc,
+            interrupt: Some(interrupt),
+        }
+    }
+
+    pub fn load_and_run(&mut self, program: Vec<u8>) -> Result<(), EmulatorError> {
         self.load(program);
         self.reset();
-        self.run();
+        self.run()
     }
 
     pub fn load(&mut self, program: Vec<u8>) {
         for i in 0..(program.len() as u16) {
-            self.mem_write(0x0000 + i, program[i as usize]);
+            self.mem_write(i, program[i as usize]);
         }
-        self.mem_write_u16(0xFFFC, 0x0000);
+        self.mem_write_u16(RESET_VECTOR, 0x0000);
     }
 
 
-    fn get_operand_address(&self, mode: &AddressingMode) -> u16 {
+    // reads the operand of a read instruction
+    // when indexing crosses a page the cpu first reads from the un-carried address, costing a cycle
+    fn read_operand(&mut self, mode: &AddressingMode) -> u8 {
+        let (addr, page_cross) = self.get_operand_address(mode);
+        if page_cross {
+            self.page_fixup_read(mode, addr, page_cross);
+        }
+        self.read(addr)
+    }
+
+    // effective address for a write or read-modify-write, which always pay for the indexed
+    // modes' fix-up cycle
+    fn write_address(&mut self, mode: &AddressingMode) -> u16 {
+        let (addr, page_cross) = self.get_operand_address(mode);
+        if matches!(mode, AddressingMode::AbsoluteX | AddressingMode::AbsoluteY | AddressingMode::IndirectY) {
+            self.page_fixup_read(mode, addr, page_cross);
+        }
+        addr
+    }
+
+    // the cycle spent carrying into the high byte of an indexed address: NMOS parts read the
+    // half-formed address, the 65C02 re-reads the last operand byte so I/O is left alone
+    fn page_fixup_read(&mut self, mode: &AddressingMode, addr: u16, page_cross: bool) {
+        if self.variant == CpuVariant::Cmos65C02 {
+            let last_operand = match mode {
+                AddressingMode::IndirectY => self.pc,
+                _ => self.pc.wrapping_add(1),
+            };
+            self.read(last_operand);
+        } else {
+            self.read(uncorrected_address(addr, page_cross));
+        }
+    }
+
+    // returns the effective address and whether indexing crossed a page boundary,
+    // after the operand and pointer fetches but before any page fix-up cycle
+    fn get_operand_address(&mut self, mode: &AddressingMode) -> (u16, bool) {
         match mode {
-            AddressingMode::Immediate => self.pc,
+            AddressingMode::Immediate => (self.pc, false),
 
-            AddressingMode::ZeroPage => self.mem_read(self.pc) as u16,
+            AddressingMode::ZeroPage => (self.read(self.pc) as u16, false),
 
-            AddressingMode::Absolute => self.mem_read_u16(self.pc),
+            AddressingMode::Absolute => (self.read_u16(self.pc), false),
 
+            // the zero page indexed modes read the unindexed address while adding
             AddressingMode::ZeroPageX => {
-                let pos = self.mem_read(self.pc);
-                let addr = pos.wrapping_add(self.index_x) as u16;
-                addr
+                let pos = self.read(self.pc);
+                self.read(pos as u16);
+                (pos.wrapping_add(self.index_x) as u16, false)
             }
 
             AddressingMode::ZeroPageY => {
-                let pos = self.mem_read(self.pc);
-                let addr = pos.wrapping_add(self.index_y) as u16;
-                addr
+                let pos = self.read(self.pc);
+                self.read(pos as u16);
+                (pos.wrapping_add(self.index_y) as u16, false)
             }
 
             AddressingMode::AbsoluteX => {
-                let base = self.mem_read_u16(self.pc);
+                let base = self.read_u16(self.pc);
                 let addr = base.wrapping_add(self.index_x as u16);
-                addr
+                (addr, page_crossed(base, addr))
             }
 
             AddressingMode::AbsoluteY => {
-                let base = self.mem_read_u16(self.pc);
+                let base = self.read_u16(self.pc);
                 let addr = base.wrapping_add(self.index_y as u16);
-                addr
+                (addr, page_crossed(base, addr))
             }
 
             AddressingMode::IndirectX => {
-                let base = self.mem_read(self.pc);
+                let base = self.read(self.pc);
+                self.read(base as u16);
 
                 let ptr: u8 = base.wrapping_add(self.index_x);
-                let lo = self.mem_read(ptr as u16);
-                let hi = self.mem_read(ptr.wrapping_add(1) as u16);
-                (hi as u16) << 8 | (lo as u16)
+                let lo = self.read(ptr as u16);
+                let hi = self.read(ptr.wrapping_add(1) as u16);
+                ((hi as u16) << 8 | (lo as u16), false)
             }
 
             AddressingMode::IndirectY => {
-                let base = self.mem_read(self.pc);
+                let base = self.read(self.pc);
 
-                let lo = self.mem_read(base as u16);
-                let hi = self.mem_read(base.wrapping_add(1) as u16);
+                let lo = self.read(base as u16);
+                let hi = self.read(base.wrapping_add(1) as u16);
                 let deref_base = (hi as u16) << 8 | (lo as u16);
-                let deref_addr = deref_base.wrapping_add(self.index_y as u16);
-                deref_addr
+                let deref = deref_base.wrapping_add(self.index_y as u16);
+                (deref, page_crossed(deref_base, deref))
             }
 
-            AddressingMode::NonAddressing => {
+            AddressingMode::ZeroPageIndirect => {
+                let base = self.read(self.pc);
+
+                let lo = self.read(base as u16);
+                let hi = self.read(base.wrapping_add(1) as u16);
+                ((hi as u16) << 8 | (lo as u16), false)
+            }
+
+            AddressingMode::Indirect
+            | AddressingMode::AbsoluteIndexedIndirect
+            | AddressingMode::ZeroPageRelative
+            | AddressingMode::Relative
+            | AddressingMode::Accumulator
+            | AddressingMode::NonAddressing => {
                 panic!("mode {:?} is not supported", mode);
             }
         }
     }
 
-    pub fn run(&mut self) {
-        self.run_with_callback(|_| {});
+    // 513 cycles, plus one to line the reads up with even cycles when the transfer starts on an odd one
+    fn oam_dma(&mut self, page: u8) {
+        self.read(self.pc); // halt
+        if self.cycles % 2 == 1 {
+            self.read(self.pc);
+        }
+        for low in 0..=0xFF {
+            let value = self.read((page as u16) << 8 | low);
+            self.write(0x2004, value);
+        }
     }
 
-    pub fn run_with_callback<F>(&mut self, mut callback: F)
-    where
-        F: FnMut(&mut CPU),
-    {
-        let opcodes: &HashMap<u8, &'static opcodes::Instruction> = &opcodes::CPU_INSTRUCTIONS_MAP;
+    // runs the opcodes the 65C02 added or changed, returning false for the ones it shares with NMOS parts
+    fn execute_cmos(&mut self, opcode: u8, instruction: &Instruction, opcode_pc: u16) -> Result<bool, EmulatorError> {
+        let mode = &instruction.mode;
+        match opcode {
+            // --- BRA ---
+            0x80 => self.branch(true),
+
+            // --- PHX, PHY, PLX, PLY ---
+            0xDA => self.stack_push(self.index_x),
+            0x5A => self.stack_push(self.index_y),
+            0xFA => {
+                self.stack_dummy_read();
+                self.index_x = self.stack_pop();
+                self.update_zero_and_negative_flags(self.index_x);
+            }
+            0x7A => {
+                self.stack_dummy_read();
+                self.index_y = self.stack_pop();
+                self.update_zero_and_negative_flags(self.index_y);
+            }
 
-        loop {
-            let opcode = self.mem_read(self.pc);
-            self.pc += 1;
-            let pc_state = self.pc;
-
-            let instruction = opcodes.get(&opcode).unwrap();
-
-            match opcode {
-                // --- LDA ---
-                0xA9 | 0xA5 | 0xB5 | 0xAD | 0xBD | 0xB9 | 0xA1 | 0xB1 => self.lda(&instruction.mode),
-                // --- LDX ---
-                0xA2 | 0xA6 | 0xB6 | 0xAE | 0xBE => self.ldx(&instruction.mode),
-                // --- LDY ---
-                0xA0 | 0xA4 | 0xB4 | 0xAC | 0xBC => self.ldy(&instruction.mode),
-                // --- STA ---
-                0x85 | 0x8D | 0x95 | 0x9D | 0x99 | 0x81 | 0x91 => self.sta(&instruction.mode),
-                // --- STX ---
-                0x86 | 0x96 | 0x8E => self.stx(&instruction.mode),
-                // --- STY ---
-                0x84 | 0x94 | 0x8C => self.sty(&instruction.mode),
-                // --- ADC ---
-                0x69 | 0x65 | 0x75 | 0x6D | 0x7D | 0x79 | 0x61 | 0x71 => self.adc(&instruction.mode),
-                // --- SBC ---
-                0xE9 | 0xE5 | 0xF5 | 0xED | 0xFD | 0xF9 | 0xE1 | 0xF1 => self.sbc(&instruction.mode),
-                // --- Compare Instructions ---
-                0xC9 | 0xC5 | 0xD5 | 0xCD | 0xDD | 0xD9 | 0xC1 | 0xD1 => self.compare(&instruction.mode, self.acc),
-                0xE0 | 0xE4 | 0xEC => self.compare(&instruction.mode, self.index_x),
-                0xC0 | 0xC4 | 0xCC => self.compare(&instruction.mode, self.index_y),
-                // --- AND ---
-                0x29 | 0x25 | 0x35 | 0x2D | 0x3D | 0x39 | 0x21 | 0x31 => self.and(&instruction.mode),
-                // --- EOR ---
-                0x49 | 0x45 | 0x55 | 0x4D | 0x5D | 0x59 | 0x41 | 0x51 => self.eor(&instruction.mode),
-                // --- ORA ---
-                0x09 | 0x05 | 0x15 | 0x0D | 0x1D | 0x19 | 0x01 | 0x11 => self.ora(&instruction.mode),
-                // --- BIT ---
-                0x24 | 0x2C => self.bit(&instruction.mode),
-                // --- INC ---
-                0xE6 | 0xEE | 0xF6 | 0xFE => self.inc(&instruction.mode),
-                // --- DEC ---
-                0xC6 | 0xCE | 0xD6 | 0xDE => self.dec(&instruction.mode),
-                // --- INX ---
-                0xE8 => self.inx(),
-                // --- INY ---
-                0xC8 => self.iny(),
-                // --- DEX ---
-                0xCA => self.dex(),
-                // --- DEY ---
-                0x88 => self.dey(),
-
-                // --- Stack Operations ---
-                0x48 => self.pha(),
-                0x68 => self.pla(),
-                0x08 => self.php(),
-                0x28 => self.plp(),
-
-                // --- Transfers ---
-                0xAA => self.tax(),
-                0xA8 => self.tay(),
-                0x8A => self.txa(),
-                0x98 => self.tya(),
-                0xBA => self.tsx(),
-                0x9A => self.txs(),
-
-                // --- Branch Instructions ---
-                0x90 => self.branch(!self.get_flag(StatusFlags::CARRY)),   // BCC
-                0xB0 => self.branch(self.get_flag(StatusFlags::CARRY)),    // BCS
-                0xF0 => self.branch(self.get_flag(StatusFlags::ZERO)),     // BEQ
-                0xD0 => self.branch(!self.get_flag(StatusFlags::ZERO)),    // BNE
-                0x30 => self.branch(self.get_flag(StatusFlags::NEGATIVE)), // BMI
-                0x10 => self.branch(!self.get_flag(StatusFlags::NEGATIVE)), // BPL
-                0x50 => self.branch(!self.get_flag(StatusFlags::OVERFLOW)), // BVC
-                0x70 => self.branch(self.get_flag(StatusFlags::OVERFLOW)), // BVS
-
-                // --- Status Flag Changes ---
-                0x18 => self.set_flag(StatusFlags::CARRY, false), // CLC
-                0x38 => self.set_flag(StatusFlags::CARRY, true),  // SEC
-                0x58 => self.set_flag(StatusFlags::INTERRUPT_DISABLE, false), // CLI
-                0x78 => self.set_flag(StatusFlags::INTERRUPT_DISABLE, true),  // SEI
-                0xD8 => self.set_flag(StatusFlags::DECIMAL_MODE, false), // CLD
-                0xF8 => self.set_flag(StatusFlags::DECIMAL_MODE, true),  // SED
-                0xB8 => self.set_flag(StatusFlags::OVERFLOW, false), //CLV
-
-                // --- JMP Absolute ---
-                0x4C => {
-                    self.pc = self.mem_read_u16(self.pc);
-                }
+            // --- STZ ---
+            0x64 | 0x74 | 0x9C | 0x9E => {
+                let addr = self.write_address(mode);
+                self.write(addr, 0);
+            }
 
-                // --- JMP Indirect ---
-                0x6C => {
-                    let operand_addr = self.mem_read_u16(self.pc);
-                    let target_addr = if operand_addr & 0x00FF == 0x00FF {
-                        // 6502 bug case: page boundary crossing
-                        let lo = self.mem_read(operand_addr);
-                        let hi = self.mem_read(operand_addr & 0xFF00); // read from start of page
-                        (hi as u16) << 8 | (lo as u16)
-                    } else {
-                        // normal case
-                        self.mem_read_u16(operand_addr)
-                    };
-                    self.pc = target_addr;
-                }
+            // --- INC A, DEC A ---
+            0x1A => {
+                self.acc = self.acc.wrapping_add(1);
+                self.update_zero_and_negative_flags(self.acc);
+            }
+            0x3A => {
+                self.acc = self.acc.wrapping_sub(1);
+                self.update_zero_and_negative_flags(self.acc);
+            }
 
-                // --- JSR ---
-                0x20 => {
-                    self.stack_push_u16(self.pc + 1);
-                    self.pc = self.mem_read_u16(self.pc);
-                }
+            // --- BIT, the immediate form only sets Z ---
+            0x89 => {
+                let value = self.read_operand(mode);
+                self.set_flag(StatusFlags::ZERO, (self.acc & value) == 0);
+            }
+            0x34 | 0x3C => self.bit(mode),
+
+            // --- TSB, TRB: Z from A AND memory, then set or clear A's bits in memory ---
+            0x04 | 0x0C => {
+                let acc = self.acc;
+                self.modify_memory(mode, |cpu, value| {
+                    cpu.set_flag(StatusFlags::ZERO, (acc & value) == 0);
+                    value | acc
+                });
+            }
+            0x14 | 0x1C => {
+                let acc = self.acc;
+                self.modify_memory(mode, |cpu, value| {
+                    cpu.set_flag(StatusFlags::ZERO, (acc & value) == 0);
+                    value & !acc
+                });
+            }
 
-                // --- RTS ---
-                0x60 => {
-                    self.pc = self.stack_pop_u16() + 1;
-                }
+            // --- Zero Page Indirect ---
+            0x12 => self.ora(mode),
+            0x32 => self.and(mode),
+            0x52 => self.eor(mode),
+            0x72 => self.adc(mode),
+            0x92 => self.sta(mode),
+            0xB2 => self.lda(mode),
+            0xD2 => self.compare(mode, self.acc),
+            0xF2 => self.sbc(mode),
+
+            // --- RMBn, SMBn ---
+            _ if opcode & 0x0F == 0x07 => {
+                let mask = 1 << ((opcode >> 4) & 0x07);
+                let set = opcode & 0x80 != 0;
+                self.modify_memory(mode, |_, value| if set { value | mask } else { value & !mask });
+            }
 
-                // --- ASL ACC ---
-                0x0A => {
-                    let mut value = self.acc;
-                    self.set_flag(StatusFlags::CARRY, (value & 0x80) > 0);
-                    value <<= 1;
-                    self.update_zero_and_negative_flags(value);
-                    self.acc = value;
-                }
-                // --- ASL Mem ---
-                0x06 | 0x16 | 0x0E | 0x1E => {
-                    let addr = self.get_operand_address(&instruction.mode);
-                    let mut value = self.mem_read(addr);
-                    self.set_flag(StatusFlags::CARRY, (value & 0x80) > 0);
-                    value <<= 1;
-                    self.update_zero_and_negative_flags(value);
-                    self.mem_write(addr, value);
-                }
+            // --- BBRn, BBSn: test a zero page bit, then branch like any other ---
+            _ if opcode & 0x0F == 0x0F => {
+                let addr = self.read(self.pc) as u16;
+                let value = self.read(addr);
+                self.read(addr);
+                self.pc = self.pc.wrapping_add(1);
 
-                // --- LSR ACC ---
-                0x4A => {
-                    let mut value = self.acc;
-                    self.set_flag(StatusFlags::CARRY, (value & 0x01) > 0);
-                    value >>= 1;
-                    self.update_zero_and_negative_flags(value);
-                    self.acc = value;
-                }
-                // --- LSR Mem ---
-                0x46 | 0x56 | 0x4E | 0x5E => {
-                    let addr = self.get_operand_address(&instruction.mode);
-                    let mut value = self.mem_read(addr);
-                    self.set_flag(StatusFlags::CARRY, (value & 0x01) > 0);
-                    value >>= 1;
-                    self.update_zero_and_negative_flags(value);
-                    self.mem_write(addr, value);
-                }
+                let bit_set = value & (1 << ((opcode >> 4) & 0x07)) != 0;
+                self.branch(bit_set == (opcode & 0x80 != 0));
+            }
 
-                // --- RTI ---
-                0x40 => {
-                    self.status = self.stack_pop();
-                    self.set_flag(StatusFlags::BREAK, false);
-                    self.set_flag(StatusFlags::BREAK2, true);
-                    self.pc = self.stack_pop_u16();
-                }
+            // --- JMP Indirect, without the page wrap bug ---
+            0x6C => {
+                let operand_addr = self.read_u16(self.pc);
+                self.read(self.pc.wrapping_add(1));
+                let target = self.read_u16(operand_addr);
+                self.jump(target);
+            }
 
-                // --- ROL ACC ---
-                0x2A => {
-                    let mut value = self.acc;
-                    let old_carry = self.get_flag(StatusFlags::CARRY);
-                    self.set_flag(StatusFlags::CARRY, (value & 0x80) > 0);
-                    value <<= 1;
-                    if old_carry {
-                        value |= 0x01;
-                    }
-                    self.update_zero_and_negative_flags(value);
-                    self.acc = value;
-                }
-                // --- ROL Mem ---
-                0x26 | 0x36 | 0x2E | 0x3E => {
-                    let addr = self.get_operand_address(&instruction.mode);
-                    let mut value = self.mem_read(addr);
-                    let old_carry = self.get_flag(StatusFlags::CARRY);
-                    self.set_flag(StatusFlags::CARRY, (value & 0x80) > 0);
-                    value <<= 1;
-                    if old_carry {
-                        value |= 0x01;
-                    }
-                    self.update_zero_and_negative_flags(value);
-                    self.mem_write(addr, value);
+            // --- JMP (abs,X) ---
+            0x7C => {
+                let base = self.read_u16(self.pc);
+                self.read(self.pc.wrapping_add(1));
+                let target = self.read_u16(base.wrapping_add(self.index_x as u16));
+                self.jump(target);
+            }
+
+            // --- Shifts abs,X, which only take the fix-up cycle when a page is crossed ---
+            0x1E | 0x3E | 0x5E | 0x7E => {
+                let op: fn(&mut Self, u8) -> u8 = match opcode {
+                    0x1E => Self::asl_value,
+                    0x3E => Self::rol_value,
+                    0x5E => Self::lsr_value,
+                    _ => Self::ror_value,
+                };
+                let (addr, page_cross) = self.get_operand_address(mode);
+                if page_cross {
+                    self.page_fixup_read(mode, addr, page_cross);
                 }
+                self.modify_at(addr, op);
+            }
+
+            // --- WAI, STP ---
+            0xCB => {
+                self.read(self.pc);
+                self.waiting = true;
+            }
+            0xDB => {
+                // stopped until reset, which is as good as jammed
+                self.pc = opcode_pc;
+                return Err(EmulatorError::CpuJammed { pc: opcode_pc, opcode });
+            }
 
-                // --- ROR ACC ---
-                0x6A => {
-                    let mut value = self.acc;
-                    let old_carry = self.get_flag(StatusFlags::CARRY);
-                    self.set_flag(StatusFlags::CARRY, (value & 0x01) > 0);
-                    value >>= 1;
-                    if old_carry {
-                        value |= 0x80;
-                    }
-                    self.update_zero_and_negative_flags(value);
-                    self.acc = value;
+            // --- Reserved NOPs, $5C spends 5 more cycles reading from $FFxx ---
+            0x5C => {
+                let addr = self.read_u16(self.pc);
+                for _ in 0..5 {
+                    self.read(0xFF00 | (addr & 0x00FF));
                 }
-                // --- ROR Mem ---
-                0x66 | 0x76 | 0x6E | 0x7E => {
-                    let addr = self.get_operand_address(&instruction.mode);
-                    let mut value = self.mem_read(addr);
-                    let old_carry = self.get_flag(StatusFlags::CARRY);
-                    self.set_flag(StatusFlags::CARRY, (value & 0x01) > 0);
-                    value >>= 1;
-                    if old_carry {
-                        value |= 0x80;
-                    }
-                    self.update_zero_and_negative_flags(value);
-                    self.mem_write(addr, value);
+            }
+            _ if instruction.unofficial => {
+                if *mode != AddressingMode::NonAddressing {
+                    self.read_operand(mode);
                 }
+            }
 
-                // --- NOP ---
-                0xEA => {/* do nothing */},
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
 
-                // --- BRK ---
-                0x00 => {
-                    self.stack_push_u16(self.pc);
+    // runs until a BRK is executed
+    pub fn run(&mut self) -> Result<(), EmulatorError> {
+        self.run_until(|_, step| step.interrupt == Some(Interrupt::Break))?;
+        Ok(())
+    }
 
-                    let mut flags = self.status;
-                    flags |= StatusFlags::BREAK;
-                    flags |= StatusFlags::BREAK2;
-                    self.stack_push(flags);
+    // steps until the predicate returns true, returning the result of the last step
+    pub fn run_until<F>(&mut self, mut predicate: F) -> Result<StepResult, EmulatorError>
+    where
+        F: FnMut(&mut Self, &StepResult) -> bool,
+    {
+        loop {
+            let result = self.step()?;
+            if predicate(self, &result) {
+                return Ok(result);
+            }
+        }
+    }
 
-                    self.set_flag(StatusFlags::INTERRUPT_DISABLE, true);
-                    self.pc = self.mem_read_u16(0xFFFE);
-                }
+    // runs whole instructions until at least `cycles` have elapsed, returning the cycles actually run
+    pub fn run_for_cycles(&mut self, cycles: u64) -> Result<u64, EmulatorError> {
+        let mut elapsed: u64 = 0;
+        while elapsed < cycles {
+            elapsed += self.step()?.cycles as u64;
+        }
+        Ok(elapsed)
+    }
 
-                _ => todo!(),
+    // executes exactly one instruction, or services a pending interrupt in its place
+    pub fn step(&mut self) -> Result<StepResult, EmulatorError> {
+        self.poll_nmi();
+        if self.nmi_pending {
+            self.nmi_pending = false;
+            return Ok(self.service_interrupt(Interrupt::Nmi));
+        }
+        if self.irq_asserted() && !self.polled_interrupt_disable {
+            return Ok(self.service_interrupt(Interrupt::Irq));
+        }
+        if self.waiting {
+            // a masked IRQ still ends WAI, execution just carries on without servicing it
+            if !self.irq_asserted() {
+                self.read(self.pc);
+                return Ok(StepResult {
+                    opcode: 0xCB,
+                    cycles: 1,
+                    pc: self.pc,
+                    interrupt: None,
+                });
+            }
+            self.waiting = false;
+        }
+
+        let start = self.cycles;
+        let opcode_pc = self.pc;
+        let opcode = self.read(opcode_pc);
+        let instruction = match opcodes::decode_table(self.variant)[opcode as usize] {
+            Some(instruction) => instruction,
+            None => return Err(EmulatorError::UnknownOpcode { pc: opcode_pc, opcode }),
+        };
+
+        self.pc = self.pc.wrapping_add(1);
+        self.jumped = false;
+        let mut interrupt = None;
+        let interrupt_disable = self.get_flag(StatusFlags::INTERRUPT_DISABLE);
+
+        // single byte instructions still fetch the following byte in their second cycle
+        if instruction.len == 1 && instruction.cycles > 1 {
+            self.read(self.pc);
+        }
+
+        let handled = self.variant == CpuVariant::Cmos65C02 && self.execute_cmos(opcode, instruction, opcode_pc)?;
+        match opcode {
+            _ if handled => {}
+
+            // --- LDA ---
+            0xA9 | 0xA5 | 0xB5 | 0xAD | 0xBD | 0xB9 | 0xA1 | 0xB1 => self.lda(&instruction.mode),
+            // --- LDX ---
+            0xA2 | 0xA6 | 0xB6 | 0xAE | 0xBE => self.ldx(&instruction.mode),
+            // --- LDY ---
+            0xA0 | 0xA4 | 0xB4 | 0xAC | 0xBC => self.ldy(&instruction.mode),
+            // --- STA ---
+            0x85 | 0x8D | 0x95 | 0x9D | 0x99 | 0x81 | 0x91 => self.sta(&instruction.mode),
+            // --- STX ---
+            0x86 | 0x96 | 0x8E => self.stx(&instruction.mode),
+            // --- STY ---
+            0x84 | 0x94 | 0x8C => self.sty(&instruction.mode),
+            // --- ADC ---
+            0x69 | 0x65 | 0x75 | 0x6D | 0x7D | 0x79 | 0x61 | 0x71 => self.adc(&instruction.mode),
+            // --- SBC ---
+            0xE9 | 0xE5 | 0xF5 | 0xED | 0xFD | 0xF9 | 0xE1 | 0xF1 => self.sbc(&instruction.mode),
+            // --- Compare Instructions ---
+            0xC9 | 0xC5 | 0xD5 | 0xCD | 0xDD | 0xD9 | 0xC1 | 0xD1 => self.compare(&instruction.mode, self.acc),
+            0xE0 | 0xE4 | 0xEC => self.compare(&instruction.mode, self.index_x),
+            0xC0 | 0xC4 | 0xCC => self.compare(&instruction.mode, self.index_y),
+            // --- AND ---
+            0x29 | 0x25 | 0x35 | 0x2D | 0x3D | 0x39 | 0x21 | 0x31 => self.and(&instruction.mode),
+            // --- EOR ---
+            0x49 | 0x45 | 0x55 | 0x4D | 0x5D | 0x59 | 0x41 | 0x51 => self.eor(&instruction.mode),
+            // --- ORA ---
+            0x09 | 0x05 | 0x15 | 0x0D | 0x1D | 0x19 | 0x01 | 0x11 => self.ora(&instruction.mode),
+            // --- BIT ---
+            0x24 | 0x2C => self.bit(&instruction.mode),
+            // --- INC ---
+            0xE6 | 0xEE | 0xF6 | 0xFE => self.inc(&instruction.mode),
+            // --- DEC ---
+            0xC6 | 0xCE | 0xD6 | 0xDE => self.dec(&instruction.mode),
+            // --- INX ---
+            0xE8 => self.inx(),
+            // --- INY ---
+            0xC8 => self.iny(),
+            // --- DEX ---
+            0xCA => self.dex(),
+            // --- DEY ---
+            0x88 => self.dey(),
+
+            // --- Stack Operations ---
+            0x48 => self.pha(),
+            0x68 => self.pla(),
+            0x08 => self.php(),
+            0x28 => self.plp(),
+
+            // --- Transfers ---
+            0xAA => self.tax(),
+            0xA8 => self.tay(),
+            0x8A => self.txa(),
+            0x98 => self.tya(),
+            0xBA => self.tsx(),
+            0x9A => self.txs(),
+
+            // --- Branch Instructions ---
+            0x90 => self.branch(!self.get_flag(StatusFlags::CARRY)),   // BCC
+            0xB0 => self.branch(self.get_flag(StatusFlags::CARRY)),    // BCS
+            0xF0 => self.branch(self.get_flag(StatusFlags::ZERO)),     // BEQ
+            0xD0 => self.branch(!self.get_flag(StatusFlags::ZERO)),    // BNE
+            0x30 => self.branch(self.get_flag(StatusFlags::NEGATIVE)), // BMI
+            0x10 => self.branch(!self.get_flag(StatusFlags::NEGATIVE)), // BPL
+            0x50 => self.branch(!self.get_flag(StatusFlags::OVERFLOW)), // BVC
+            0x70 => self.branch(self.get_flag(StatusFlags::OVERFLOW)), // BVS
+
+            // --- Status Flag Changes ---
+            0x18 => self.set_flag(StatusFlags::CARRY, false), // CLC
+            0x38 => self.set_flag(StatusFlags::CARRY, true),  // SEC
+            0x58 => self.set_flag(StatusFlags::INTERRUPT_DISABLE, false), // CLI
+            0x78 => self.set_flag(StatusFlags::INTERRUPT_DISABLE, true),  // SEI
+            0xD8 => self.set_flag(StatusFlags::DECIMAL_MODE, false), // CLD
+            0xF8 => self.set_flag(StatusFlags::DECIMAL_MODE, true),  // SED
+            0xB8 => self.set_flag(StatusFlags::OVERFLOW, false), //CLV
+
+            // --- JMP Absolute ---
+            0x4C => {
+                let target = self.read_u16(self.pc);
+                self.jump(target);
+            }
+
+            // --- JMP Indirect ---
+            0x6C => {
+                let operand_addr = self.read_u16(self.pc);
+                let target_addr = if operand_addr & 0x00FF == 0x00FF {
+                    // 6502 bug case: page boundary crossing
+                    let lo = self.read(operand_addr);
+                    let hi = self.read(operand_addr & 0xFF00); // read from start of page
+                    (hi as u16) << 8 | (lo as u16)
+                } else {
+                    // normal case
+                    self.read_u16(operand_addr)
+                };
+                self.jump(target_addr);
+            }
+
+            // --- JSR ---
+            // the low byte is fetched before the return address is pushed, the high byte after
+            0x20 => {
+                let lo = self.read(self.pc) as u16;
+                self.pc = self.pc.wrapping_add(1);
+                self.stack_dummy_read();
+                self.stack_push_u16(self.pc);
+                let hi = self.read(self.pc) as u16;
+                self.jump((hi << 8) | lo);
+            }
+
+            // --- RTS ---
+            0x60 => {
+                self.stack_dummy_read();
+                let return_addr = self.stack_pop_u16();
+                self.read(return_addr);
+                self.jump(return_addr.wrapping_add(1));
+            }
+
+            // --- ASL ---
+            0x0A => self.acc = self.asl_value(self.acc),
+            0x06 | 0x16 | 0x0E | 0x1E => {
+                self.modify_memory(&instruction.mode, Self::asl_value);
+            }
+
+            // --- LSR ---
+            0x4A => self.acc = self.lsr_value(self.acc),
+            0x46 | 0x56 | 0x4E | 0x5E => {
+                self.modify_memory(&instruction.mode, Self::lsr_value);
+            }
+
+            // --- ROL ---
+            0x2A => self.acc = self.rol_value(self.acc),
+            0x26 | 0x36 | 0x2E | 0x3E => {
+                self.modify_memory(&instruction.mode, Self::rol_value);
             }
 
-            // handle setting pc for everything that isnt jumps and branches
-            if pc_state == self.pc {
-                self.pc += (instruction.len -1) as u16;
+            // --- ROR ---
+            0x6A => self.acc = self.ror_value(self.acc),
+            0x66 | 0x76 | 0x6E | 0x7E => {
+                self.modify_memory(&instruction.mode, Self::ror_value);
             }
 
-            callback(self);
+            // --- RTI ---
+            0x40 => {
+                self.stack_dummy_read();
+                self.status = self.stack_pop();
+                self.set_flag(StatusFlags::BREAK, false);
+                self.set_flag(StatusFlags::BREAK2, true);
+                let return_addr = self.stack_pop_u16();
+                self.jump(return_addr);
+            }
+
+            // --- NOP ---
+            0xEA => {/* do nothing */},
+
+            // --- BRK ---
+            0x00 => {
+                self.pc = self.pc.wrapping_add(1); // BRK skips a padding byte
+                self.push_interrupt_state(true);
+                interrupt = Some(self.vector_interrupt(Interrupt::Break));
+                self.jumped = true;
+            }
+
+            // --- Unofficial NOPs, the multi-byte ones still read their operand ---
+            0x1A | 0x3A | 0x5A | 0x7A | 0xDA | 0xFA => {/* do nothing */},
+            0x80 | 0x82 | 0x89 | 0xC2 | 0xE2
+            | 0x04 | 0x44 | 0x64 | 0x14 | 0x34 | 0x54 | 0x74 | 0xD4 | 0xF4
+            | 0x0C | 0x1C | 0x3C | 0x5C | 0x7C | 0xDC | 0xFC => {
+                self.read_operand(&instruction.mode);
+            }
+
+            // --- Unofficial Load/Store ---
+            0xA7 | 0xB7 | 0xAF | 0xBF | 0xA3 | 0xB3 => self.lax(&instruction.mode),
+            0x87 | 0x97 | 0x8F | 0x83 => self.sax(&instruction.mode),
+
+            // --- Unofficial Read-Modify-Write ---
+            0xC7 | 0xD7 | 0xCF | 0xDF | 0xDB | 0xC3 | 0xD3 => self.dcp(&instruction.mode),
+            0xE7 | 0xF7 | 0xEF | 0xFF | 0xFB | 0xE3 | 0xF3 => self.isb(&instruction.mode),
+            0x07 | 0x17 | 0x0F | 0x1F | 0x1B | 0x03 | 0x13 => self.slo(&instruction.mode),
+            0x27 | 0x37 | 0x2F | 0x3F | 0x3B | 0x23 | 0x33 => self.rla(&instruction.mode),
+            0x47 | 0x57 | 0x4F | 0x5F | 0x5B | 0x43 | 0x53 => self.sre(&instruction.mode),
+            0x67 | 0x77 | 0x6F | 0x7F | 0x7B | 0x63 | 0x73 => self.rra(&instruction.mode),
+
+            // --- Unofficial Immediate ---
+            0xEB => self.sbc(&instruction.mode),
+            0x0B | 0x2B => self.anc(&instruction.mode),
+            0x4B => self.alr(&instruction.mode),
+            0x6B => self.arr(&instruction.mode),
+            0xCB => self.axs(&instruction.mode),
+
+            // --- Unofficial Unstable ---
+            0x8B => self.xaa(&instruction.mode),
+            0xAB => self.lxa(&instruction.mode),
+            0x9E => self.store_and_high(&instruction.mode, self.index_x), // SHX
+            0x9C => self.store_and_high(&instruction.mode, self.index_y), // SHY
+            0x9F | 0x93 => self.store_and_high(&instruction.mode, self.acc & self.index_x), // AHX
+            0x9B => { // TAS
+                self.sp = self.acc & self.index_x;
+                self.store_and_high(&instruction.mode, self.sp);
+            }
+            0xBB => self.las(&instruction.mode),
+
+            // --- JAM ---
+            0x02 | 0x12 | 0x22 | 0x32 | 0x42 | 0x52 | 0x62 | 0x72 | 0x92 | 0xB2 | 0xD2 | 0xF2 => {
+                self.pc = opcode_pc;
+                return Err(EmulatorError::CpuJammed { pc: opcode_pc, opcode });
+            }
+        }
+
+        // handle setting pc for everything that isnt jumps and branches
+        if !self.jumped {
+            self.pc = self.pc.wrapping_add((instruction.len - 1) as u16);
         }
+
+        // CLI, SEI and PLP change I after the interrupt poll, so their effect on IRQs is one instruction late
+        self.polled_interrupt_disable = match opcode {
+            0x58 | 0x78 | 0x28 => interrupt_disable,
+            _ => self.get_flag(StatusFlags::INTERRUPT_DISABLE),
+        };
+
+        if let Some(page) = self.bus.take_oam_dma() {
+            self.oam_dma(page);
+        }
+
+        Ok(StepResult {
+            opcode,
+            cycles: (self.cycles - start) as u16,
+            pc: self.pc,
+            interrupt,
+        })
     }
 }
\ No newline at end of file
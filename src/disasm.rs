@@ -0,0 +1,227 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::cpu::{AddressingMode, Memory};
+use crate::opcodes::{self, Instruction};
+
+// data bytes not reached as code are grouped this many to a line
+const DATA_BYTES_PER_LINE: usize = 8;
+
+// one disassembled instruction, or data bytes when `instruction` is None
+pub struct Line {
+    pub address: u16,
+    pub bytes: Vec<u8>,
+    pub instruction: Option<&'static Instruction>,
+}
+
+impl Line {
+    fn data(address: u16, bytes: &[u8]) -> Line {
+        Line {
+            address,
+            bytes: bytes.to_vec(),
+            instruction: None,
+        }
+    }
+
+    // the 8 or 16 bit operand as stored after the opcode
+    fn operand(&self) -> u16 {
+        match self.bytes.len() {
+            2 => self.bytes[1] as u16,
+            3 => (self.bytes[2] as u16) << 8 | self.bytes[1] as u16,
+            _ => 0,
+        }
+    }
+
+    // where control can go besides falling through: branch, JMP and JSR destinations
+    pub fn target(&self) -> Option<u16> {
+        let instruction = self.instruction?;
+        match instruction.mode {
+            AddressingMode::Relative => {
+                let offset = self.bytes[1] as i8;
+                Some(self.address.wrapping_add(2).wrapping_add(offset as u16))
+            }
+            AddressingMode::ZeroPageRelative => {
+                let offset = self.bytes[2] as i8;
+                Some(self.address.wrapping_add(3).wrapping_add(offset as u16))
+            }
+            AddressingMode::Absolute if matches!(instruction.mnemonic, "JMP" | "JSR") => {
+                Some(self.operand())
+            }
+            _ => None,
+        }
+    }
+
+    // whether execution can continue with the next instruction
+    fn falls_through(&self) -> bool {
+        match self.instruction {
+            Some(instruction) => !matches!(instruction.mnemonic, "JMP" | "BRA" | "RTS" | "RTI" | "BRK" | "JAM" | "STP"),
+            None => false,
+        }
+    }
+
+    // assembly text, using label names for any operand address that has one
+    pub fn text(&self, labels: &BTreeMap<u16, String>) -> String {
+        let instruction = match self.instruction {
+            Some(instruction) => instruction,
+            None => {
+                let bytes: Vec<String> = self.bytes.iter().map(|byte| format!("${:02X}", byte)).collect();
+                return format!(".byte {}", bytes.join(", "));
+            }
+        };
+
+        let operand = self.operand();
+        let address = |addr: u16| match labels.get(&addr) {
+            Some(label) => label.clone(),
+            None => format!("${:04X}", addr),
+        };
+
+        let operand_text = match instruction.mode {
+            AddressingMode::NonAddressing => String::new(),
+            AddressingMode::Accumulator => "A".to_string(),
+            AddressingMode::Immediate => format!("#${:02X}", operand),
+            AddressingMode::ZeroPage => format!("${:02X}", operand),
+            AddressingMode::ZeroPageX => format!("${:02X},X", operand),
+            AddressingMode::ZeroPageY => format!("${:02X},Y", operand),
+            AddressingMode::Absolute => address(operand),
+            AddressingMode::AbsoluteX => format!("{},X", address(operand)),
+            AddressingMode::AbsoluteY => format!("{},Y", address(operand)),
+            AddressingMode::IndirectX => format!("(${:02X},X)", operand),
+            AddressingMode::IndirectY => format!("(${:02X}),Y", operand),
+            AddressingMode::Indirect => format!("({})", address(operand)),
+            AddressingMode::Relative => address(self.target().unwrap_or(0)),
+            AddressingMode::ZeroPageIndirect => format!("(${:02X})", operand),
+            AddressingMode::AbsoluteIndexedIndirect => format!("({},X)", address(operand)),
+            AddressingMode::ZeroPageRelative => {
+                format!("${:02X},{}", self.bytes[1], address(self.target().unwrap_or(0)))
+            }
+        };
+
+        if operand_text.is_empty() {
+            instruction.mnemonic.to_string()
+        } else {
+            format!("{} {}", instruction.mnemonic, operand_text)
+        }
+    }
+}
+
+// decodes the instruction at `offset`, or None if it runs off the end of the slice
+fn decode(bytes: &[u8], origin: u16, offset: usize) -> Option<Line> {
+    let instruction = opcodes::DECODE_TABLE[bytes[offset] as usize]?;
+    let end = offset + instruction.len as usize;
+    if end > bytes.len() {
+        return None;
+    }
+    Some(Line {
+        address: origin.wrapping_add(offset as u16),
+        bytes: bytes[offset..end].to_vec(),
+        instruction: Some(instruction),
+    })
+}
+
+// linear sweep: every byte is treated as code, starting at `origin`
+pub fn disassemble(bytes: &[u8], origin: u16) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let line = decode(bytes, origin, offset)
+            .unwrap_or_else(|| Line::data(origin.wrapping_add(offset as u16), &bytes[offset..=offset]));
+        offset += line.bytes.len();
+        lines.push(line);
+    }
+    lines
+}
+
+// linear sweep over a live address range (inclusive), peeking so I/O registers are untouched
+pub fn disassemble_memory<M: Memory>(memory: &M, start: u16, end: u16) -> Vec<Line> {
+    let bytes: Vec<u8> = (start..=end).map(|addr| memory.peek(addr)).collect();
+    disassemble(&bytes, start)
+}
+
+// follows control flow from the entry points, anything never reached is listed as data
+pub fn disassemble_from(bytes: &[u8], origin: u16, entry_points: &[u16]) -> Vec<Line> {
+    let in_range = |addr: u16| {
+        let offset = addr.wrapping_sub(origin) as usize;
+        if offset < bytes.len() { Some(offset) } else { None }
+    };
+
+    let mut code: BTreeMap<usize, Line> = BTreeMap::new();
+    let mut pending: Vec<u16> = entry_points.to_vec();
+    while let Some(addr) = pending.pop() {
+        let mut offset = match in_range(addr) {
+            Some(offset) => offset,
+            None => continue,
+        };
+        while !code.contains_key(&offset) {
+            let line = match decode(bytes, origin, offset) {
+                Some(line) => line,
+                None => break,
+            };
+            if let Some(target) = line.target() {
+                pending.push(target);
+            }
+            let falls_through = line.falls_through();
+            let next = offset + line.bytes.len();
+            code.insert(offset, line);
+            if !falls_through {
+                break;
+            }
+            offset = next;
+        }
+    }
+
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        match code.remove(&offset) {
+            Some(line) => {
+                offset += line.bytes.len();
+                lines.push(line);
+            }
+            None => {
+                let mut end = offset + 1;
+                while end < bytes.len() && end - offset < DATA_BYTES_PER_LINE && !code.contains_key(&end) {
+                    end += 1;
+                }
+                lines.push(Line::data(origin.wrapping_add(offset as u16), &bytes[offset..end]));
+                offset = end;
+            }
+        }
+    }
+    lines
+}
+
+// renders a listing, labelling every jump/branch target inside it plus any named addresses
+//
+// RESET:
+//   C000  4C F5 C5  JMP L_C5F5
+pub fn format_listing(lines: &[Line], names: &[(u16, &str)]) -> String {
+    let addresses: BTreeSet<u16> = lines.iter().map(|line| line.address).collect();
+
+    let mut labels: BTreeMap<u16, String> = lines
+        .iter()
+        .filter_map(Line::target)
+        .filter(|target| addresses.contains(target))
+        .map(|target| (target, format!("L_{:04X}", target)))
+        .collect();
+    for (addr, name) in names {
+        labels.insert(*addr, name.to_string());
+    }
+
+    let mut out = String::new();
+    for line in lines {
+        if let Some(label) = labels.get(&line.address) {
+            out.push_str(&format!("{}:\n", label));
+        }
+        // data lines already spell their bytes out in the .byte directive
+        let hex = match line.instruction {
+            Some(_) => line
+                .bytes
+                .iter()
+                .map(|byte| format!("{:02X}", byte))
+                .collect::<Vec<String>>()
+                .join(" "),
+            None => String::new(),
+        };
+        out.push_str(&format!("  {:04X}  {:8}  {}\n", line.address, hex, line.text(&labels)));
+    }
+    out
+}
@@ -0,0 +1,48 @@
+use std::fmt;
+
+// the ways a ROM image can fail validation before it is even looked at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidRomKind {
+    MissingNesTag,
+    UnsupportedFormat,
+    MissingPrgRom, // every board's banking is taken modulo the PRG size
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmulatorError {
+    UnknownOpcode { pc: u16, opcode: u8 },
+    CpuJammed { pc: u16, opcode: u8 }, // one of the KIL/JAM opcodes halted the cpu
+    InvalidRom(InvalidRomKind),
+    TruncatedRom { expected: usize, actual: usize },
+    UnsupportedMapper(u16),
+}
+
+impl fmt::Display for InvalidRomKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidRomKind::MissingNesTag => write!(f, "missing NES header tag"),
+            InvalidRomKind::UnsupportedFormat => write!(f, "unsupported NES file format"),
+            InvalidRomKind::MissingPrgRom => write!(f, "no PRG ROM"),
+        }
+    }
+}
+
+impl fmt::Display for EmulatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmulatorError::UnknownOpcode { pc, opcode } => {
+                write!(f, "unknown opcode ${:02X} at ${:04X}", opcode, pc)
+            }
+            EmulatorError::CpuJammed { pc, opcode } => {
+                write!(f, "cpu jammed by opcode ${:02X} at ${:04X}", opcode, pc)
+            }
+            EmulatorError::InvalidRom(kind) => write!(f, "invalid ROM: {}", kind),
+            EmulatorError::TruncatedRom { expected, actual } => {
+                write!(f, "truncated ROM: expected {} bytes, got {}", expected, actual)
+            }
+            EmulatorError::UnsupportedMapper(mapper) => write!(f, "unsupported mapper {}", mapper),
+        }
+    }
+}
+
+impl std::error::Error for EmulatorError {}
@@ -0,0 +1,61 @@
+use crate::cpu::{CpuVariant, Memory, CPU};
+use crate::error::EmulatorError;
+use crate::memory::FlatMemory;
+
+// Klaus Dormann's 6502_functional_test.bin, as built with the default options
+// (which include the decimal mode tests, so it needs CpuVariant::Nmos6502)
+pub const FUNCTIONAL_TEST_START: u16 = 0x0400;
+pub const FUNCTIONAL_TEST_SUCCESS: u16 = 0x3469;
+
+// how a self-checking test program ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Trapped(u16), // pc stuck on a jump or branch to itself
+    TimedOut,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Report {
+    pub outcome: Outcome,
+    pub passed: bool,
+    pub instructions: u64,
+    pub cycles: u64,
+}
+
+// steps until an instruction leaves pc where it was, the way test ROMs signal pass or fail
+pub fn run_until_trap<M: Memory>(cpu: &mut CPU<M>, max_instructions: u64) -> Result<(Outcome, u64), EmulatorError> {
+    let mut instructions: u64 = 0;
+    while instructions < max_instructions {
+        let pc = cpu.pc;
+        let result = cpu.step()?;
+        instructions += 1;
+        if result.pc == pc && result.interrupt.is_none() {
+            return Ok((Outcome::Trapped(pc), instructions));
+        }
+    }
+    Ok((Outcome::TimedOut, instructions))
+}
+
+// loads a full 64 KiB test image at $0000, starts at `start` and passes if it traps at `success`
+pub fn run_test_image(
+    image: &[u8],
+    variant: CpuVariant,
+    start: u16,
+    success: u16,
+    max_instructions: u64,
+) -> Result<Report, EmulatorError> {
+    let mut memory = FlatMemory::new();
+    memory.load(0x0000, image);
+
+    let mut cpu = CPU::with_variant(memory, variant);
+    cpu.reset();
+    cpu.pc = start;
+
+    let (outcome, instructions) = run_until_trap(&mut cpu, max_instructions)?;
+    Ok(Report {
+        outcome,
+        passed: outcome == Outcome::Trapped(success),
+        instructions,
+        cycles: cpu.cycles,
+    })
+}
@@ -0,0 +1,16 @@
+#![allow(non_snake_case)] // the crate keeps the project's name
+
+pub mod asm;
+pub mod bus;
+pub mod cartridge;
+pub mod conformance;
+pub mod cpu;
+pub mod disasm;
+pub mod error;
+pub mod harness;
+pub mod joypad;
+pub mod mapper;
+pub mod memory;
+pub mod opcodes;
+pub mod save;
+pub mod trace;
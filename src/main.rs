@@ -1,13 +1,11 @@
 // NOTE: this version of main has been directly copied from the original, due to me not fully grasping how th ebus is supposed to be used
-pub mod bus;
-pub mod cartridge;
-pub mod cpu;
-pub mod opcodes;
-
-use bus::Bus;
-use cartridge::Rom;
-use cpu::Memory;
-use cpu::CPU;
+use redNES::bus::Bus;
+use redNES::cartridge::Rom;
+use redNES::cpu::Memory;
+use redNES::conformance;
+use redNES::cpu::CPU;
+use redNES::disasm;
+use redNES::save;
 use rand::Rng;
 
 use sdl2::event::Event;
@@ -16,12 +14,6 @@ use sdl2::pixels::Color;
 use sdl2::pixels::PixelFormatEnum;
 use sdl2::EventPump;
 
-#[macro_use]
-extern crate lazy_static;
-
-#[macro_use]
-extern crate bitflags;
-
 fn color(byte: u8) -> Color {
     match byte {
         0 => sdl2::pixels::Color::BLACK,
@@ -40,7 +32,7 @@ fn read_screen_state(cpu: &CPU, frame: &mut [u8; 32 * 3 * 32]) -> bool {
     let mut frame_idx = 0;
     let mut update = false;
     for i in 0x0200..0x600 {
-        let color_idx = cpu.mem_read(i as u16);
+        let color_idx = cpu.peek(i as u16);
         let (b1, b2, b3) = color(color_idx).rgb();
         if frame[frame_idx] != b1 || frame[frame_idx + 1] != b2 || frame[frame_idx + 2] != b3 {
             frame[frame_idx] = b1;
@@ -53,14 +45,15 @@ fn read_screen_state(cpu: &CPU, frame: &mut [u8; 32 * 3 * 32]) -> bool {
     update
 }
 
-fn handle_user_input(cpu: &mut CPU, event_pump: &mut EventPump) {
+// returns true once the user has asked to quit
+fn handle_user_input(cpu: &mut CPU, event_pump: &mut EventPump) -> bool {
     for event in event_pump.poll_iter() {
         match event {
             Event::Quit { .. }
             | Event::KeyDown {
                 keycode: Some(Keycode::Escape),
                 ..
-            } => std::process::exit(0),
+            } => return true,
             Event::KeyDown {
                 keycode: Some(Keycode::W),
                 ..
@@ -88,9 +81,107 @@ fn handle_user_input(cpu: &mut CPU, event_pump: &mut EventPump) {
             _ => { /* do nothing */ }
         }
     }
+    false
 }
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("disasm") => match args.get(2) {
+            Some(path) => disasm_rom(path),
+            None => {
+                eprintln!("usage: redNES disasm <rom.nes>");
+                std::process::exit(2);
+            }
+        },
+        Some("singlestep") => match args.get(2) {
+            Some(dir) => run_singlestep(dir, args.iter().any(|arg| arg == "--cycles")),
+            None => {
+                eprintln!("usage: redNES singlestep <dir of xx.json files> [--cycles]");
+                std::process::exit(2);
+            }
+        },
+        _ => run_snake(),
+    }
+}
+
+fn load_rom(path: &str) -> Rom {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", path, err);
+            std::process::exit(1);
+        }
+    };
+    match Rom::new(&bytes) {
+        Ok(rom) => rom,
+        Err(err) => {
+            eprintln!("Failed to load {}: {}", path, err);
+            std::process::exit(1);
+        }
+    }
+}
+
+// prints the PRG banks, following code from the reset/NMI/IRQ vectors in the power-on mapping
+// ($8000 = first bank, $C000 = last bank); any other banks are swept linearly at $8000
+fn disasm_rom(path: &str) {
+    const BANK_SIZE: usize = 0x4000;
+
+    let rom = load_rom(path);
+    let banks: Vec<&[u8]> = rom.prg_rom.chunks(BANK_SIZE).collect();
+    if banks.is_empty() {
+        eprintln!("{} has no PRG ROM", path);
+        std::process::exit(1);
+    }
+
+    let last = banks[banks.len() - 1];
+    let (origin, image) = if banks.len() == 1 {
+        (0xC000, last.to_vec())
+    } else {
+        (0x8000, [banks[0], last].concat())
+    };
+
+    // a PRG ROM shorter than a bank may not reach the vectors at all
+    let vector = |addr: u16| {
+        let offset = (addr - origin) as usize;
+        let lo = *image.get(offset)?;
+        let hi = *image.get(offset + 1)?;
+        Some((hi as u16) << 8 | lo as u16)
+    };
+
+    println!("; PRG banks 0 and {} at ${:04X}", banks.len() - 1, origin);
+    let mut names = Vec::new();
+    for (addr, name) in [(0xFFFA, "NMI"), (0xFFFE, "IRQ"), (0xFFFC, "RESET")] {
+        match vector(addr) {
+            Some(target) => names.push((target, name)),
+            None => println!("; {} vector: ????", name),
+        }
+    }
+    let entry_points: Vec<u16> = names.iter().map(|(addr, _)| *addr).collect();
+
+    let lines = disasm::disassemble_from(&image, origin, &entry_points);
+    print!("{}", disasm::format_listing(&lines, &names));
+
+    for (i, bank) in banks.iter().enumerate().take(banks.len() - 1).skip(1) {
+        println!();
+        println!("; PRG bank {} at $8000", i);
+        let lines = disasm::disassemble(bank, 0x8000);
+        print!("{}", disasm::format_listing(&lines, &[]));
+    }
+}
+
+// runs the SingleStepTests JSON vectors in `dir` and prints the per-opcode pass/fail matrix
+fn run_singlestep(dir: &str, check_cycles: bool) {
+    match conformance::run_directory(std::path::Path::new(dir), check_cycles) {
+        Ok(results) => print!("{}", conformance::format_matrix(&results)),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run_snake() {
     // init sdl2
     let sdl_context = sdl2::init().unwrap();
     let video_subsystem = sdl_context.video().unwrap();
@@ -110,19 +201,30 @@ fn main() {
         .unwrap();
 
     //load the game
-    let bytes: Vec<u8> = std::fs::read("snake.nes").unwrap();
-    let rom = Rom::new(&bytes).unwrap();
+    let rom = load_rom("snake.nes");
 
-    let bus = Bus::new(rom);
+    let mut bus = match Bus::new(rom) {
+        Ok(bus) => bus,
+        Err(err) => {
+            eprintln!("Failed to load snake.nes: {}", err);
+            std::process::exit(1);
+        }
+    };
+    let save_path = save::path_for(std::path::Path::new("snake.nes"));
+    if let Err(err) = save::load(bus.mapper_mut(), &save_path) {
+        eprintln!("Failed to load {}: {}", save_path.display(), err);
+    }
     let mut cpu = CPU::new(bus);
     cpu.reset();
 
-    let mut screen_state = [0 as u8; 32 * 3 * 32];
+    let mut screen_state = [0_u8; 32 * 3 * 32];
     let mut rng = rand::thread_rng();
 
     // run the game cycle
-    cpu.run_with_callback(move |cpu| {
-        handle_user_input(cpu, &mut event_pump);
+    let result = cpu.run_until(move |cpu, _| {
+        if handle_user_input(cpu, &mut event_pump) {
+            return true;
+        }
 
         cpu.mem_write(0xfe, rng.gen_range(1, 16));
 
@@ -135,5 +237,14 @@ fn main() {
         }
 
         std::thread::sleep(std::time::Duration::new(0, 70_000));
+        false
     });
+
+    if let Err(err) = save::store(cpu.bus.mapper(), &save_path) {
+        eprintln!("Failed to write {}: {}", save_path.display(), err);
+    }
+    if let Err(err) = result {
+        eprintln!("Emulation stopped: {}", err);
+        std::process::exit(1);
+    }
 }
\ No newline at end of file
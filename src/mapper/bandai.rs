@@ -0,0 +1,213 @@
+use crate::cartridge::{Mirroring, Rom};
+use crate::mapper::eeprom::{Eeprom, EepromKind};
+use crate::mapper::Mapper;
+
+const PRG_BANK_SIZE: usize = 0x4000;
+const CHR_BANK_SIZE: usize = 0x0400;
+const PRG_RAM_SIZE: usize = 0x2000;
+const CHR_RAM_SIZE: usize = 0x2000;
+const OUTER_PRG_SIZE: usize = 0x40000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BandaiBoard {
+    // FCG-1/2 with registers at $6000, or LZ93D50 + 24C02 at $8000; iNES can't tell them
+    // apart, so both ranges work
+    Mapper16,
+    // LZ93D50 with battery-backed PRG-RAM and 512 KiB of PRG, CHR-RAM
+    Mapper153,
+    // LZ93D50 + 24C01
+    Mapper159,
+}
+
+// mappers 16, 153 and 159: Bandai FCG-1/2 and LZ93D50, sixteen registers mirrored across
+// the range
+//
+// $x0-$x7 CHR banks, 1 KiB (on mapper 153, bit 0 selects the 256 KiB PRG half)
+// $x8     PRG bank at $8000, the last bank fixed at $C000
+// $x9     mirroring
+// $xA     IRQ control, bit 0 enables; also acknowledges
+// $xB/$xC IRQ counter low/high byte (a latch copied in by $xA on the LZ93D50)
+// $xD     EEPROM  RDC-----  R: read enable, D: SDA, C: SCL; PRG-RAM enable on mapper 153
+pub struct Bandai {
+    board: BandaiBoard,
+    prg_rom: Vec<u8>,
+    prg_ram: Vec<u8>,
+    chr: Vec<u8>,
+    chr_is_ram: bool,
+    battery: bool,
+    eeprom: Option<Eeprom>,
+
+    chr_banks: [u8; 8],
+    prg_bank: u8,
+    outer_prg_bank: u8,
+    prg_ram_enabled: bool,
+    mirroring: Mirroring,
+
+    irq_latch: u16,
+    irq_counter: u16,
+    irq_enabled: bool,
+    irq_pending: bool,
+}
+
+impl Bandai {
+    pub fn new(board: BandaiBoard, rom: Rom) -> Self {
+        let chr_is_ram = rom.chr_rom.is_empty();
+        let eeprom = match board {
+            BandaiBoard::Mapper16 => Some(Eeprom::new(EepromKind::C24C02)),
+            BandaiBoard::Mapper153 => None,
+            BandaiBoard::Mapper159 => Some(Eeprom::new(EepromKind::X24C01)),
+        };
+        Bandai {
+            board,
+            prg_rom: rom.prg_rom,
+            prg_ram: vec![0; PRG_RAM_SIZE],
+            chr: if chr_is_ram { vec![0; CHR_RAM_SIZE] } else { rom.chr_rom },
+            chr_is_ram,
+            battery: rom.battery,
+            eeprom,
+            chr_banks: [0; 8],
+            prg_bank: 0,
+            outer_prg_bank: 0,
+            prg_ram_enabled: false,
+            mirroring: rom.screen_mirroring,
+            irq_latch: 0,
+            irq_counter: 0,
+            irq_enabled: false,
+            irq_pending: false,
+        }
+    }
+
+    fn prg_offset(&self, address: u16) -> usize {
+        let outer = self.outer_prg_bank as usize * OUTER_PRG_SIZE;
+        let banks = self.prg_rom.len().min(OUTER_PRG_SIZE) / PRG_BANK_SIZE;
+        let bank = match address {
+            0x8000..=0xBFFF => (self.prg_bank & 0x0F) as usize,
+            _ => banks.saturating_sub(1),
+        };
+        (outer + bank * PRG_BANK_SIZE + (address as usize & (PRG_BANK_SIZE - 1))) % self.prg_rom.len()
+    }
+
+    fn chr_offset(&self, address: u16) -> usize {
+        if self.chr_is_ram {
+            return (address & 0x1FFF) as usize;
+        }
+        let bank = self.chr_banks[((address >> 10) & 0x07) as usize] as usize;
+        (bank * CHR_BANK_SIZE + (address as usize & (CHR_BANK_SIZE - 1))) % self.chr.len()
+    }
+
+    // `latched` is the LZ93D50 behaviour, where the counter is only loaded through $xA
+    fn write_register(&mut self, register: u16, value: u8, latched: bool) {
+        match register {
+            0x0..=0x7 => {
+                self.chr_banks[register as usize] = value;
+                if self.board == BandaiBoard::Mapper153 {
+                    self.outer_prg_bank = value & 0x01;
+                }
+            }
+            0x8 => self.prg_bank = value,
+            0x9 => {
+                self.mirroring = match value & 0x03 {
+                    0 => Mirroring::VERTICAL,
+                    1 => Mirroring::HORIZONTAL,
+                    2 => Mirroring::SINGLE_SCREEN_LOWER,
+                    _ => Mirroring::SINGLE_SCREEN_UPPER,
+                };
+            }
+            0xA => {
+                self.irq_enabled = value & 0x01 != 0;
+                self.irq_pending = false;
+                if latched {
+                    self.irq_counter = self.irq_latch;
+                }
+            }
+            0xB | 0xC => {
+                let shift = if register == 0xB { 0 } else { 8 };
+                self.irq_latch = (self.irq_latch & !(0xFF << shift)) | (value as u16) << shift;
+                if !latched {
+                    self.irq_counter = self.irq_latch;
+                }
+            }
+            0xD => {
+                if let Some(eeprom) = self.eeprom.as_mut() {
+                    eeprom.write(value & 0x20 != 0, value & 0x40 != 0);
+                }
+                self.prg_ram_enabled = value & 0x20 != 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Mapper for Bandai {
+    fn cpu_peek(&self, address: u16) -> Option<u8> {
+        match address {
+            0x6000..=0x7FFF if self.board == BandaiBoard::Mapper153 => {
+                if self.prg_ram_enabled { Some(self.prg_ram[(address - 0x6000) as usize]) } else { None }
+            }
+            // only bit 4, the EEPROM's data line, is driven
+            0x6000..=0x7FFF => self.eeprom.as_ref().map(|eeprom| (eeprom.output() as u8) << 4),
+            0x8000..=0xFFFF => Some(self.prg_rom[self.prg_offset(address)]),
+            _ => None,
+        }
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) {
+        match address {
+            0x6000..=0x7FFF if self.board == BandaiBoard::Mapper153 && self.prg_ram_enabled => {
+                self.prg_ram[(address - 0x6000) as usize] = value;
+            }
+            0x6000..=0x7FFF if self.board == BandaiBoard::Mapper16 => {
+                self.write_register(address & 0x0F, value, false);
+            }
+            0x8000..=0xFFFF => self.write_register(address & 0x0F, value, true),
+            _ => {}
+        }
+    }
+
+    fn ppu_read(&mut self, address: u16) -> u8 {
+        self.chr[self.chr_offset(address)]
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let offset = self.chr_offset(address);
+            self.chr[offset] = value;
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn irq_pending(&self) -> bool {
+        self.irq_pending
+    }
+
+    // counts down every cpu cycle while enabled, interrupting when it reaches 0
+    fn cpu_clock(&mut self) {
+        if !self.irq_enabled {
+            return;
+        }
+        self.irq_counter = self.irq_counter.wrapping_sub(1);
+        if self.irq_counter == 0 {
+            self.irq_pending = true;
+        }
+    }
+
+    // the EEPROM is what gets saved on the boards that have one, whatever the battery flag says
+    fn save_ram(&self) -> Option<&[u8]> {
+        match &self.eeprom {
+            Some(eeprom) => Some(eeprom.data()),
+            None if self.battery => Some(&self.prg_ram),
+            None => None,
+        }
+    }
+
+    fn save_ram_mut(&mut self) -> Option<&mut [u8]> {
+        match &mut self.eeprom {
+            Some(eeprom) => Some(eeprom.data_mut()),
+            None if self.battery => Some(&mut self.prg_ram),
+            None => None,
+        }
+    }
+}
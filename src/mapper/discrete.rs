@@ -0,0 +1,106 @@
+use crate::cartridge::{Mirroring, Rom};
+use crate::mapper::Mapper;
+
+const CHR_BANK_SIZE: usize = 0x2000;
+const CHR_RAM_SIZE: usize = 0x2000;
+
+// boards built from a single latch of off-the-shelf logic; any write to $8000-$FFFF loads it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscreteBoard {
+    UxRom,       // mapper 2:  16 KiB PRG bank at $8000, last bank fixed at $C000
+    CnRom,       // mapper 3:  8 KiB CHR bank
+    AxRom,       // mapper 7:  ---M-PPP  32 KiB PRG bank, M picks the single-screen nametable
+    ColorDreams, // mapper 11: CCCC--PP  32 KiB PRG bank, 8 KiB CHR bank
+    BnRom,       // mapper 34: 32 KiB PRG bank (the NINA-001 board sharing the number isn't handled)
+    GxRom,       // mapper 66: --PP--CC  32 KiB PRG bank, 8 KiB CHR bank
+}
+
+pub struct Discrete {
+    board: DiscreteBoard,
+    prg_rom: Vec<u8>,
+    chr: Vec<u8>,
+    chr_is_ram: bool,
+    mirroring: Mirroring,
+    latch: u8,
+    // without a ROM enable tied to R/W, the ROM drives the bus during the write too and
+    // the latch sees the value ANDed with the byte at that address
+    bus_conflicts: bool,
+}
+
+impl Discrete {
+    pub fn new(board: DiscreteBoard, rom: Rom) -> Self {
+        let chr_is_ram = rom.chr_rom.is_empty();
+        Discrete {
+            board,
+            prg_rom: rom.prg_rom,
+            chr: if chr_is_ram { vec![0; CHR_RAM_SIZE] } else { rom.chr_rom },
+            chr_is_ram,
+            mirroring: rom.screen_mirroring,
+            latch: 0,
+            bus_conflicts: false,
+        }
+    }
+
+    pub fn with_bus_conflicts(mut self, bus_conflicts: bool) -> Self {
+        self.bus_conflicts = bus_conflicts;
+        self
+    }
+
+    fn prg_offset(&self, address: u16) -> usize {
+        let latch = self.latch as usize;
+        let (bank, size) = match self.board {
+            DiscreteBoard::UxRom if address >= 0xC000 => ((self.prg_rom.len() / 0x4000).saturating_sub(1), 0x4000),
+            DiscreteBoard::UxRom => (latch, 0x4000),
+            DiscreteBoard::CnRom => (0, 0x8000),
+            DiscreteBoard::AxRom => (latch & 0x07, 0x8000),
+            DiscreteBoard::ColorDreams => (latch & 0x03, 0x8000),
+            DiscreteBoard::BnRom => (latch, 0x8000),
+            DiscreteBoard::GxRom => ((latch >> 4) & 0x03, 0x8000),
+        };
+        (bank * size + (address as usize & (size - 1))) % self.prg_rom.len()
+    }
+
+    fn chr_offset(&self, address: u16) -> usize {
+        let bank = match self.board {
+            DiscreteBoard::CnRom => self.latch,
+            DiscreteBoard::ColorDreams => self.latch >> 4,
+            DiscreteBoard::GxRom => self.latch & 0x03,
+            _ => 0,
+        } as usize;
+        (bank * CHR_BANK_SIZE + (address as usize & (CHR_BANK_SIZE - 1))) % self.chr.len()
+    }
+}
+
+impl Mapper for Discrete {
+    fn cpu_peek(&self, address: u16) -> Option<u8> {
+        match address {
+            0x8000..=0xFFFF => Some(self.prg_rom[self.prg_offset(address)]),
+            _ => None,
+        }
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) {
+        if address >= 0x8000 {
+            self.latch = if self.bus_conflicts { value & self.prg_rom[self.prg_offset(address)] } else { value };
+        }
+    }
+
+    fn ppu_read(&mut self, address: u16) -> u8 {
+        self.chr[self.chr_offset(address)]
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let offset = self.chr_offset(address);
+            self.chr[offset] = value;
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        match self.board {
+            DiscreteBoard::AxRom if self.latch & 0x10 != 0 => Mirroring::SINGLE_SCREEN_UPPER,
+            DiscreteBoard::AxRom => Mirroring::SINGLE_SCREEN_LOWER,
+            _ => self.mirroring,
+        }
+    }
+}
@@ -0,0 +1,181 @@
+// the serial EEPROMs on Bandai boards, driven a bit at a time through the mapper's SCL/SDA lines
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EepromKind {
+    // 128 bytes; after a start come 7 address bits and the R/W bit, then data, all LSB first
+    X24C01,
+    // 256 bytes, standard I2C: device address, word address, then data, all MSB first
+    C24C02,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Idle,
+    DeviceAddress, // 24C02 only
+    WordAddress,   // the whole command byte on the 24C01
+    Write,
+    Read,
+}
+
+pub struct Eeprom {
+    kind: EepromKind,
+    data: Vec<u8>,
+    mode: Mode,
+    next_mode: Mode,
+    address: u8,
+    shift: u8,
+    bit: u8, // 0-7 data bits, 8 the acknowledge clock
+    ack: bool,
+    scl: bool,
+    sda: bool,
+    output: bool, // the chip's SDA driver, released (high) unless pulling low
+}
+
+impl Eeprom {
+    pub fn new(kind: EepromKind) -> Self {
+        let size = match kind {
+            EepromKind::X24C01 => 128,
+            EepromKind::C24C02 => 256,
+        };
+        Eeprom {
+            kind,
+            data: vec![0xFF; size],
+            mode: Mode::Idle,
+            next_mode: Mode::Idle,
+            address: 0,
+            shift: 0,
+            bit: 0,
+            ack: false,
+            scl: false,
+            sda: true,
+            output: true,
+        }
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn data_mut(&mut self) -> &mut [u8] {
+        &mut self.data
+    }
+
+    pub fn output(&self) -> bool {
+        self.output
+    }
+
+    // the lines as the board drives them after a register write
+    pub fn write(&mut self, scl: bool, sda: bool) {
+        let (last_scl, last_sda) = (self.scl, self.sda);
+        self.scl = scl;
+        self.sda = sda;
+
+        if last_scl && scl && last_sda && !sda {
+            self.start();
+        } else if last_scl && scl && !last_sda && sda {
+            // stop
+            self.mode = Mode::Idle;
+            self.output = true;
+        } else if !last_scl && scl {
+            self.rising_edge();
+        } else if last_scl && !scl {
+            self.falling_edge();
+        }
+    }
+
+    fn start(&mut self) {
+        self.mode = match self.kind {
+            EepromKind::X24C01 => Mode::WordAddress,
+            EepromKind::C24C02 => Mode::DeviceAddress,
+        };
+        self.shift = 0;
+        self.bit = 0;
+        self.output = true;
+    }
+
+    fn msb_first(&self) -> bool {
+        self.kind == EepromKind::C24C02
+    }
+
+    fn rising_edge(&mut self) {
+        match self.mode {
+            Mode::Idle => {}
+            Mode::Read if self.bit < 8 => self.bit += 1,
+            Mode::Read => {
+                // the master acknowledges to keep reading
+                self.bit = 0;
+                if self.sda {
+                    self.mode = Mode::Idle;
+                } else {
+                    self.address = self.next_address();
+                }
+            }
+            _ if self.bit < 8 => {
+                let sda = self.sda as u8;
+                if self.msb_first() {
+                    self.shift = (self.shift << 1) | sda;
+                } else {
+                    self.shift |= sda << self.bit;
+                }
+                self.bit += 1;
+                if self.bit == 8 {
+                    self.byte_received();
+                }
+            }
+            _ => {
+                self.bit = 0;
+                self.shift = 0;
+                self.mode = self.next_mode;
+            }
+        }
+    }
+
+    fn falling_edge(&mut self) {
+        self.output = match self.mode {
+            Mode::Idle => true,
+            Mode::Read if self.bit < 8 => {
+                let bit = if self.msb_first() { 7 - self.bit } else { self.bit };
+                (self.data[self.address as usize] >> bit) & 1 != 0
+            }
+            Mode::Read => true,
+            _ if self.bit == 8 => !self.ack,
+            _ => true,
+        };
+    }
+
+    fn byte_received(&mut self) {
+        let byte = self.shift;
+        let mask = (self.data.len() - 1) as u8;
+        self.ack = true;
+        self.next_mode = match self.mode {
+            Mode::DeviceAddress if byte & 0xF0 != 0xA0 => {
+                self.ack = false;
+                Mode::Idle
+            }
+            Mode::DeviceAddress if byte & 0x01 != 0 => Mode::Read,
+            Mode::DeviceAddress => Mode::WordAddress,
+            Mode::WordAddress if self.kind == EepromKind::X24C01 => {
+                self.address = byte & mask;
+                if byte & 0x80 != 0 { Mode::Read } else { Mode::Write }
+            }
+            Mode::WordAddress => {
+                self.address = byte & mask;
+                Mode::Write
+            }
+            _ => {
+                self.data[self.address as usize] = byte;
+                // writes wrap around within a page: 4 bytes on the 24C01, 8 on the 24C02
+                let page = match self.kind {
+                    EepromKind::X24C01 => 0x03,
+                    EepromKind::C24C02 => 0x07,
+                };
+                self.address = (self.address & !page) | (self.address.wrapping_add(1) & page);
+                Mode::Write
+            }
+        };
+    }
+
+    fn next_address(&self) -> u8 {
+        self.address.wrapping_add(1) & (self.data.len() - 1) as u8
+    }
+}
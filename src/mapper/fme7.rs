@@ -0,0 +1,171 @@
+use crate::cartridge::{Mirroring, Rom};
+use crate::mapper::Mapper;
+
+const PRG_BANK_SIZE: usize = 0x2000;
+const CHR_BANK_SIZE: usize = 0x0400;
+const PRG_RAM_SIZE: usize = 0x2000;
+const CHR_RAM_SIZE: usize = 0x2000;
+
+// mapper 69: Sunsoft FME-7, and the 5A/5B which add audio (not emulated)
+//
+// $8000 command, $A000 parameter for it:
+//   $0-$7 CHR banks, 1 KiB
+//   $8    bank at $6000  ERBBBBBB  E: RAM enabled, R: RAM instead of ROM
+//   $9-$B PRG banks at $8000/$A000/$C000, the last bank is fixed at $E000
+//   $C    mirroring
+//   $D    IRQ control    C------I  C: counter running, I: IRQ enabled; also acknowledges
+//   $E/$F IRQ counter low/high byte
+pub struct Fme7 {
+    prg_rom: Vec<u8>,
+    prg_ram: Vec<u8>,
+    chr: Vec<u8>,
+    chr_is_ram: bool,
+    battery: bool,
+
+    command: u8,
+    chr_banks: [u8; 8],
+    ram_bank: u8,
+    prg_banks: [u8; 3],
+    mirroring: Mirroring,
+
+    irq_counter: u16,
+    irq_counter_enabled: bool,
+    irq_enabled: bool,
+    irq_pending: bool,
+}
+
+impl Fme7 {
+    pub fn new(rom: Rom) -> Self {
+        let chr_is_ram = rom.chr_rom.is_empty();
+        Fme7 {
+            prg_rom: rom.prg_rom,
+            prg_ram: vec![0; PRG_RAM_SIZE],
+            chr: if chr_is_ram { vec![0; CHR_RAM_SIZE] } else { rom.chr_rom },
+            chr_is_ram,
+            battery: rom.battery,
+            command: 0,
+            chr_banks: [0; 8],
+            ram_bank: 0,
+            prg_banks: [0; 3],
+            mirroring: rom.screen_mirroring,
+            irq_counter: 0,
+            irq_counter_enabled: false,
+            irq_enabled: false,
+            irq_pending: false,
+        }
+    }
+
+    fn ram_selected(&self) -> bool {
+        self.ram_bank & 0x40 != 0
+    }
+
+    fn ram_enabled(&self) -> bool {
+        self.ram_bank & 0xC0 == 0xC0
+    }
+
+    fn prg_offset(&self, address: u16) -> usize {
+        let bank = match address {
+            0x6000..=0x7FFF => (self.ram_bank & 0x3F) as usize,
+            0x8000..=0xDFFF => (self.prg_banks[((address - 0x8000) >> 13) as usize] & 0x3F) as usize,
+            _ => (self.prg_rom.len() / PRG_BANK_SIZE).saturating_sub(1),
+        };
+        (bank * PRG_BANK_SIZE + (address as usize & (PRG_BANK_SIZE - 1))) % self.prg_rom.len()
+    }
+
+    fn prg_ram_offset(&self, address: u16) -> usize {
+        let bank = (self.ram_bank & 0x3F) as usize;
+        (bank * PRG_BANK_SIZE + (address as usize & (PRG_BANK_SIZE - 1))) % self.prg_ram.len()
+    }
+
+    fn chr_offset(&self, address: u16) -> usize {
+        let bank = self.chr_banks[((address >> 10) & 0x07) as usize] as usize;
+        (bank * CHR_BANK_SIZE + (address as usize & (CHR_BANK_SIZE - 1))) % self.chr.len()
+    }
+
+    fn write_parameter(&mut self, value: u8) {
+        match self.command {
+            0x0..=0x7 => self.chr_banks[self.command as usize] = value,
+            0x8 => self.ram_bank = value,
+            0x9..=0xB => self.prg_banks[(self.command - 0x9) as usize] = value,
+            0xC => {
+                self.mirroring = match value & 0x03 {
+                    0 => Mirroring::VERTICAL,
+                    1 => Mirroring::HORIZONTAL,
+                    2 => Mirroring::SINGLE_SCREEN_LOWER,
+                    _ => Mirroring::SINGLE_SCREEN_UPPER,
+                };
+            }
+            0xD => {
+                self.irq_enabled = value & 0x01 != 0;
+                self.irq_counter_enabled = value & 0x80 != 0;
+                self.irq_pending = false;
+            }
+            0xE => self.irq_counter = (self.irq_counter & 0xFF00) | value as u16,
+            _ => self.irq_counter = (self.irq_counter & 0x00FF) | (value as u16) << 8,
+        }
+    }
+}
+
+impl Mapper for Fme7 {
+    fn cpu_peek(&self, address: u16) -> Option<u8> {
+        match address {
+            // RAM selected but disabled leaves the bus floating
+            0x6000..=0x7FFF if self.ram_selected() => {
+                if self.ram_enabled() { Some(self.prg_ram[self.prg_ram_offset(address)]) } else { None }
+            }
+            0x6000..=0xFFFF => Some(self.prg_rom[self.prg_offset(address)]),
+            _ => None,
+        }
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) {
+        match address {
+            0x6000..=0x7FFF if self.ram_enabled() => {
+                let offset = self.prg_ram_offset(address);
+                self.prg_ram[offset] = value;
+            }
+            0x8000..=0x9FFF => self.command = value & 0x0F,
+            0xA000..=0xBFFF => self.write_parameter(value),
+            // $C000-$FFFF are the 5B's audio registers
+            _ => {}
+        }
+    }
+
+    fn ppu_read(&mut self, address: u16) -> u8 {
+        self.chr[self.chr_offset(address)]
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let offset = self.chr_offset(address);
+            self.chr[offset] = value;
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn irq_pending(&self) -> bool {
+        self.irq_pending
+    }
+
+    // the counter runs down every cpu cycle and interrupts when it wraps past 0
+    fn cpu_clock(&mut self) {
+        if !self.irq_counter_enabled {
+            return;
+        }
+        self.irq_counter = self.irq_counter.wrapping_sub(1);
+        if self.irq_counter == 0xFFFF && self.irq_enabled {
+            self.irq_pending = true;
+        }
+    }
+
+    fn save_ram(&self) -> Option<&[u8]> {
+        if self.battery { Some(&self.prg_ram) } else { None }
+    }
+
+    fn save_ram_mut(&mut self) -> Option<&mut [u8]> {
+        if self.battery { Some(&mut self.prg_ram) } else { None }
+    }
+}
@@ -0,0 +1,187 @@
+use crate::cartridge::{Mirroring, Rom};
+use crate::mapper::Mapper;
+
+const PRG_BANK_SIZE: usize = 0x4000;
+const CHR_BANK_SIZE: usize = 0x1000;
+const PRG_RAM_BANK_SIZE: usize = 0x2000;
+const CHR_RAM_SIZE: usize = 0x2000;
+const OUTER_PRG_SIZE: usize = 0x40000; // 256 KiB, the most the PRG bank register can reach
+
+// mapper 1: registers are loaded one bit at a time through a 5-bit shift register
+//
+// $8000-$9FFF control    ---CPPMM  C: CHR 8K/4K, PP: PRG mode, MM: mirroring
+// $A000-$BFFF CHR bank 0 ---CCCCC  on SUROM/SXROM bit 4 selects the 256 KiB PRG half
+// $C000-$DFFF CHR bank 1 ---CCCCC    and bits 2-3 the 8 KiB PRG-RAM bank
+// $E000-$FFFF PRG bank   ---RPPPP  R: PRG-RAM disabled
+pub struct Mmc1 {
+    prg_rom: Vec<u8>,
+    prg_ram: Vec<u8>,
+    chr: Vec<u8>,
+    chr_is_ram: bool,
+    battery: bool,
+
+    shift: u8,
+    shift_count: u8,
+    control: u8,
+    chr_bank_0: u8,
+    chr_bank_1: u8,
+    prg_bank: u8,
+
+    cycle: u64, // cpu cycles seen, to spot writes on consecutive cycles
+    last_write_cycle: Option<u64>,
+}
+
+impl Mmc1 {
+    pub fn new(rom: Rom) -> Self {
+        let chr_is_ram = rom.chr_rom.is_empty();
+        Mmc1 {
+            prg_rom: rom.prg_rom,
+            prg_ram: vec![0; PRG_RAM_BANK_SIZE],
+            chr: if chr_is_ram { vec![0; CHR_RAM_SIZE] } else { rom.chr_rom },
+            chr_is_ram,
+            battery: rom.battery,
+            shift: 0,
+            shift_count: 0,
+            control: 0x0C, // powers up with the last bank fixed at $C000
+            chr_bank_0: 0,
+            chr_bank_1: 0,
+            prg_bank: 0,
+            cycle: 0,
+            last_write_cycle: None,
+        }
+    }
+
+    // SXROM style boards with 32 KiB of PRG-RAM bank it through CHR bank 0
+    pub fn with_prg_ram_size(mut self, size: usize) -> Self {
+        self.prg_ram = vec![0; size.max(PRG_RAM_BANK_SIZE)];
+        self
+    }
+
+    fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x8000..=0x9FFF => self.control = value,
+            0xA000..=0xBFFF => self.chr_bank_0 = value,
+            0xC000..=0xDFFF => self.chr_bank_1 = value,
+            _ => self.prg_bank = value,
+        }
+    }
+
+    fn prg_ram_enabled(&self) -> bool {
+        self.prg_bank & 0x10 == 0
+    }
+
+    fn prg_ram_offset(&self, address: u16) -> usize {
+        let banks = self.prg_ram.len() / PRG_RAM_BANK_SIZE;
+        let bank = ((self.chr_bank_0 >> 2) & 0x03) as usize % banks;
+        bank * PRG_RAM_BANK_SIZE + (address - 0x6000) as usize
+    }
+
+    fn prg_offset(&self, address: u16) -> usize {
+        // 512 KiB boards (SUROM/SXROM) pick the 256 KiB half with CHR bank 0 bit 4
+        let outer = if self.prg_rom.len() > OUTER_PRG_SIZE {
+            ((self.chr_bank_0 >> 4) & 1) as usize * (OUTER_PRG_SIZE / PRG_BANK_SIZE)
+        } else {
+            0
+        };
+        let last = (self.prg_rom.len().min(OUTER_PRG_SIZE) / PRG_BANK_SIZE).saturating_sub(1);
+        let select = (self.prg_bank & 0x0F) as usize;
+
+        let upper_half = address >= 0xC000;
+        let bank = match ((self.control >> 2) & 0x03, upper_half) {
+            (0 | 1, _) => (select & !1) | upper_half as usize, // 32 KiB at $8000
+            (2, false) => 0, // first bank fixed at $8000
+            (3, true) => last, // last bank fixed at $C000
+            _ => select,
+        };
+        let offset = (outer + bank) * PRG_BANK_SIZE + (address as usize & (PRG_BANK_SIZE - 1));
+        offset % self.prg_rom.len()
+    }
+
+    fn chr_offset(&self, address: u16) -> usize {
+        let bank = if self.control & 0x10 == 0 {
+            // 8 KiB mode ignores the low bit
+            (self.chr_bank_0 & 0x1E) as usize | (address >= 0x1000) as usize
+        } else if address < 0x1000 {
+            self.chr_bank_0 as usize
+        } else {
+            self.chr_bank_1 as usize
+        };
+        (bank * CHR_BANK_SIZE + (address as usize & (CHR_BANK_SIZE - 1))) % self.chr.len()
+    }
+}
+
+impl Mapper for Mmc1 {
+    fn cpu_peek(&self, address: u16) -> Option<u8> {
+        match address {
+            0x6000..=0x7FFF if self.prg_ram_enabled() => Some(self.prg_ram[self.prg_ram_offset(address)]),
+            0x8000..=0xFFFF => Some(self.prg_rom[self.prg_offset(address)]),
+            _ => None,
+        }
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) {
+        match address {
+            0x6000..=0x7FFF if self.prg_ram_enabled() => {
+                let offset = self.prg_ram_offset(address);
+                self.prg_ram[offset] = value;
+            }
+            0x8000..=0xFFFF => {
+                // only the first of writes on back-to-back cycles counts, so the dummy write
+                // of a read-modify-write instruction is all the serial port sees
+                let consecutive = self.last_write_cycle == Some(self.cycle.wrapping_sub(1));
+                self.last_write_cycle = Some(self.cycle);
+                if consecutive {
+                    return;
+                }
+
+                if value & 0x80 != 0 {
+                    self.shift = 0;
+                    self.shift_count = 0;
+                    self.control |= 0x0C;
+                    return;
+                }
+
+                self.shift |= (value & 1) << self.shift_count;
+                self.shift_count += 1;
+                if self.shift_count == 5 {
+                    self.write_register(address, self.shift);
+                    self.shift = 0;
+                    self.shift_count = 0;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn ppu_read(&mut self, address: u16) -> u8 {
+        self.chr[self.chr_offset(address)]
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let offset = self.chr_offset(address);
+            self.chr[offset] = value;
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        match self.control & 0x03 {
+            0 => Mirroring::SINGLE_SCREEN_LOWER,
+            1 => Mirroring::SINGLE_SCREEN_UPPER,
+            2 => Mirroring::VERTICAL,
+            _ => Mirroring::HORIZONTAL,
+        }
+    }
+
+    fn cpu_clock(&mut self) {
+        self.cycle += 1;
+    }
+
+    fn save_ram(&self) -> Option<&[u8]> {
+        if self.battery { Some(&self.prg_ram) } else { None }
+    }
+
+    fn save_ram_mut(&mut self) -> Option<&mut [u8]> {
+        if self.battery { Some(&mut self.prg_ram) } else { None }
+    }
+}
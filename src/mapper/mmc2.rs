@@ -0,0 +1,140 @@
+use crate::cartridge::{Mirroring, Rom};
+use crate::mapper::Mapper;
+
+const CHR_BANK_SIZE: usize = 0x1000;
+const PRG_RAM_SIZE: usize = 0x2000;
+const CHR_RAM_SIZE: usize = 0x2000;
+
+// mappers 9 (MMC2) and 10 (MMC4): each 4 KiB pattern table has two CHR banks, picked by a
+// latch that flips when the PPU fetches tile $FD or $FE from it
+//
+// $A000 PRG bank: 8 KiB at $8000 with the last 24 KiB fixed (MMC2), or 16 KiB with the
+//       last 16 KiB fixed (MMC4)
+// $B000/$C000 CHR bank for $0000 when latch 0 is $FD/$FE
+// $D000/$E000 CHR bank for $1000 when latch 1 is $FD/$FE
+// $F000 mirroring -------M
+pub struct Mmc2 {
+    mmc4: bool,
+    prg_rom: Vec<u8>,
+    prg_ram: Vec<u8>,
+    chr: Vec<u8>,
+    chr_is_ram: bool,
+    battery: bool,
+
+    prg_bank: u8,
+    chr_banks: [[u8; 2]; 2], // [pattern table][latch is $FE]
+    latches: [bool; 2],      // true when latched on $FE
+    mirroring: Mirroring,
+}
+
+impl Mmc2 {
+    pub fn new(rom: Rom) -> Self {
+        Mmc2::with_chip(rom, false)
+    }
+
+    pub fn mmc4(rom: Rom) -> Self {
+        Mmc2::with_chip(rom, true)
+    }
+
+    fn with_chip(rom: Rom, mmc4: bool) -> Self {
+        let chr_is_ram = rom.chr_rom.is_empty();
+        Mmc2 {
+            mmc4,
+            prg_rom: rom.prg_rom,
+            // only the MMC4 boards have PRG-RAM
+            prg_ram: if mmc4 { vec![0; PRG_RAM_SIZE] } else { Vec::new() },
+            chr: if chr_is_ram { vec![0; CHR_RAM_SIZE] } else { rom.chr_rom },
+            chr_is_ram,
+            battery: rom.battery,
+            prg_bank: 0,
+            chr_banks: [[0; 2]; 2],
+            latches: [true; 2],
+            mirroring: rom.screen_mirroring,
+        }
+    }
+
+    fn prg_offset(&self, address: u16) -> usize {
+        let offset = if self.mmc4 {
+            match address {
+                0x8000..=0xBFFF => self.prg_bank as usize * 0x4000 + (address as usize & 0x3FFF),
+                _ => self.prg_rom.len().saturating_sub(0x4000) + (address as usize & 0x3FFF),
+            }
+        } else {
+            match address {
+                0x8000..=0x9FFF => self.prg_bank as usize * 0x2000 + (address as usize & 0x1FFF),
+                _ => self.prg_rom.len().saturating_sub(0x6000) + (address as usize - 0xA000),
+            }
+        };
+        offset % self.prg_rom.len()
+    }
+
+    fn chr_offset(&self, address: u16) -> usize {
+        let table = ((address >> 12) & 1) as usize;
+        let bank = self.chr_banks[table][self.latches[table] as usize] as usize;
+        (bank * CHR_BANK_SIZE + (address as usize & (CHR_BANK_SIZE - 1))) % self.chr.len()
+    }
+
+    // the latch changes after the fetch, so the $FD/$FE tile itself still comes from the old bank
+    fn update_latch(&mut self, address: u16) {
+        let address = address & 0x1FFF;
+        // the MMC2 only watches one address of the first table, the MMC4 the whole tile row
+        let (fd, fe) = if address < 0x1000 && !self.mmc4 {
+            (address == 0x0FD8, address == 0x0FE8)
+        } else {
+            (address & 0x0FF8 == 0x0FD8, address & 0x0FF8 == 0x0FE8)
+        };
+        if fd || fe {
+            self.latches[(address >> 12) as usize] = fe;
+        }
+    }
+}
+
+impl Mapper for Mmc2 {
+    fn cpu_peek(&self, address: u16) -> Option<u8> {
+        match address {
+            0x6000..=0x7FFF if self.mmc4 => Some(self.prg_ram[(address - 0x6000) as usize]),
+            0x8000..=0xFFFF => Some(self.prg_rom[self.prg_offset(address)]),
+            _ => None,
+        }
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) {
+        match address {
+            0x6000..=0x7FFF if self.mmc4 => self.prg_ram[(address - 0x6000) as usize] = value,
+            0xA000..=0xAFFF => self.prg_bank = value & 0x0F,
+            0xB000..=0xBFFF => self.chr_banks[0][0] = value & 0x1F,
+            0xC000..=0xCFFF => self.chr_banks[0][1] = value & 0x1F,
+            0xD000..=0xDFFF => self.chr_banks[1][0] = value & 0x1F,
+            0xE000..=0xEFFF => self.chr_banks[1][1] = value & 0x1F,
+            0xF000..=0xFFFF => {
+                self.mirroring = if value & 1 == 0 { Mirroring::VERTICAL } else { Mirroring::HORIZONTAL };
+            }
+            _ => {}
+        }
+    }
+
+    fn ppu_read(&mut self, address: u16) -> u8 {
+        let value = self.chr[self.chr_offset(address)];
+        self.update_latch(address);
+        value
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let offset = self.chr_offset(address);
+            self.chr[offset] = value;
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn save_ram(&self) -> Option<&[u8]> {
+        if self.battery && self.mmc4 { Some(&self.prg_ram) } else { None }
+    }
+
+    fn save_ram_mut(&mut self) -> Option<&mut [u8]> {
+        if self.battery && self.mmc4 { Some(&mut self.prg_ram) } else { None }
+    }
+}
@@ -0,0 +1,229 @@
+use crate::cartridge::{Mirroring, Rom};
+use crate::mapper::Mapper;
+
+const PRG_BANK_SIZE: usize = 0x2000;
+const CHR_BANK_SIZE: usize = 0x0400;
+const PRG_RAM_SIZE: usize = 0x2000;
+const CHR_RAM_SIZE: usize = 0x2000;
+
+// PPU A12 has to stay low for this many cpu cycles before a rise clocks the scanline counter,
+// which filters out the toggling during sprite fetches
+const A12_FILTER_CYCLES: u64 = 3;
+
+// the chips disagree on what a counter reloaded with 0 does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mmc3Revision {
+    // MMC3A and the Sharp MMC3B: only a reload requested through $C001 raises the IRQ,
+    // a counter that keeps reloading with 0 stays quiet
+    A,
+    // later MMC3B and MMC3C: the IRQ is raised on every clock that leaves the counter at 0
+    #[default]
+    B,
+}
+
+// mapper 4: eight bank registers behind a bank select, and a counter of PPU A12 rises
+// (one per scanline while rendering) that can interrupt the cpu
+//
+// $8000 even bank select  CP---RRR  C: CHR A12 inversion, P: PRG mode, R: register
+// $8001 odd  bank data
+// $A000 even mirroring    -------M
+// $A001 odd  PRG-RAM      EW------  E: enabled, W: write protected
+// $C000 even IRQ latch,   $C001 odd IRQ reload
+// $E000 even IRQ disable, $E001 odd IRQ enable
+pub struct Mmc3 {
+    prg_rom: Vec<u8>,
+    prg_ram: Vec<u8>,
+    chr: Vec<u8>,
+    chr_is_ram: bool,
+    four_screen: bool,
+    battery: bool,
+    revision: Mmc3Revision,
+
+    bank_select: u8,
+    registers: [u8; 8],
+    mirroring: Mirroring,
+    prg_ram_enabled: bool,
+    prg_ram_write_protect: bool,
+
+    irq_latch: u8,
+    irq_counter: u8,
+    irq_reload: bool,
+    irq_enabled: bool,
+    irq_pending: bool,
+
+    cycle: u64,
+    a12_low_since: Option<u64>, // cpu cycle A12 last fell, None while it is high
+}
+
+impl Mmc3 {
+    pub fn new(rom: Rom) -> Self {
+        let chr_is_ram = rom.chr_rom.is_empty();
+        let four_screen = rom.screen_mirroring == Mirroring::FOUR_SCREEN;
+        Mmc3 {
+            prg_rom: rom.prg_rom,
+            prg_ram: vec![0; PRG_RAM_SIZE],
+            chr: if chr_is_ram { vec![0; CHR_RAM_SIZE] } else { rom.chr_rom },
+            chr_is_ram,
+            four_screen,
+            battery: rom.battery,
+            revision: Mmc3Revision::default(),
+            bank_select: 0,
+            registers: [0, 2, 4, 5, 6, 7, 0, 1],
+            mirroring: if four_screen { Mirroring::FOUR_SCREEN } else { Mirroring::VERTICAL },
+            prg_ram_enabled: true,
+            prg_ram_write_protect: false,
+            irq_latch: 0,
+            irq_counter: 0,
+            irq_reload: false,
+            irq_enabled: false,
+            irq_pending: false,
+            cycle: 0,
+            a12_low_since: Some(0),
+        }
+    }
+
+    pub fn with_revision(mut self, revision: Mmc3Revision) -> Self {
+        self.revision = revision;
+        self
+    }
+
+    fn prg_offset(&self, address: u16) -> usize {
+        let banks = self.prg_rom.len() / PRG_BANK_SIZE;
+        let second_last = banks.saturating_sub(2);
+        let r6 = (self.registers[6] & 0x3F) as usize;
+        let r7 = (self.registers[7] & 0x3F) as usize;
+
+        // PRG mode 1 swaps which of $8000 and $C000 is switchable
+        let swapped = self.bank_select & 0x40 != 0;
+        let bank = match (address >> 13) & 0x03 {
+            0 => if swapped { second_last } else { r6 },
+            1 => r7,
+            2 => if swapped { r6 } else { second_last },
+            _ => banks.saturating_sub(1),
+        };
+        (bank * PRG_BANK_SIZE + (address as usize & (PRG_BANK_SIZE - 1))) % self.prg_rom.len()
+    }
+
+    fn chr_offset(&self, address: u16) -> usize {
+        // inversion puts the two 2 KiB banks at $1000 and the four 1 KiB banks at $0000
+        let address = if self.bank_select & 0x80 != 0 { address ^ 0x1000 } else { address } & 0x1FFF;
+        let bank = match address >> 10 {
+            0 => self.registers[0] & 0xFE,
+            1 => self.registers[0] | 0x01,
+            2 => self.registers[1] & 0xFE,
+            3 => self.registers[1] | 0x01,
+            slot => self.registers[(slot - 2) as usize],
+        } as usize;
+        (bank * CHR_BANK_SIZE + (address as usize & (CHR_BANK_SIZE - 1))) % self.chr.len()
+    }
+
+    fn clock_irq_counter(&mut self) {
+        let reloaded = self.irq_reload;
+        let was_zero = self.irq_counter == 0;
+        if was_zero || self.irq_reload {
+            self.irq_counter = self.irq_latch;
+            self.irq_reload = false;
+        } else {
+            self.irq_counter -= 1;
+        }
+
+        let fire = match self.revision {
+            Mmc3Revision::A => self.irq_counter == 0 && (!was_zero || reloaded),
+            Mmc3Revision::B => self.irq_counter == 0,
+        };
+        if fire && self.irq_enabled {
+            self.irq_pending = true;
+        }
+    }
+
+    // every PPU pattern fetch goes through here so the counter can see A12 rise
+    fn watch_a12(&mut self, address: u16) {
+        if address & 0x1000 != 0 {
+            let cycle = self.cycle;
+            if self.a12_low_since.take().is_some_and(|since| cycle - since >= A12_FILTER_CYCLES) {
+                self.clock_irq_counter();
+            }
+        } else if self.a12_low_since.is_none() {
+            self.a12_low_since = Some(self.cycle);
+        }
+    }
+}
+
+impl Mapper for Mmc3 {
+    fn cpu_peek(&self, address: u16) -> Option<u8> {
+        match address {
+            0x6000..=0x7FFF if self.prg_ram_enabled => Some(self.prg_ram[(address - 0x6000) as usize]),
+            0x8000..=0xFFFF => Some(self.prg_rom[self.prg_offset(address)]),
+            _ => None,
+        }
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) {
+        let even = address & 1 == 0;
+        match address {
+            0x6000..=0x7FFF if self.prg_ram_enabled && !self.prg_ram_write_protect => {
+                self.prg_ram[(address - 0x6000) as usize] = value;
+            }
+            0x8000..=0x9FFF if even => self.bank_select = value,
+            0x8000..=0x9FFF => self.registers[(self.bank_select & 0x07) as usize] = value,
+            // four-screen boards wire the nametables themselves
+            0xA000..=0xBFFF if even && !self.four_screen => {
+                self.mirroring = if value & 1 == 0 { Mirroring::VERTICAL } else { Mirroring::HORIZONTAL };
+            }
+            0xA000..=0xBFFF if !even => {
+                self.prg_ram_enabled = value & 0x80 != 0;
+                self.prg_ram_write_protect = value & 0x40 != 0;
+            }
+            0xC000..=0xDFFF if even => self.irq_latch = value,
+            0xC000..=0xDFFF => {
+                self.irq_counter = 0;
+                self.irq_reload = true;
+            }
+            0xE000..=0xFFFF if even => {
+                self.irq_enabled = false;
+                self.irq_pending = false;
+            }
+            0xE000..=0xFFFF => self.irq_enabled = true,
+            _ => {}
+        }
+    }
+
+    fn ppu_read(&mut self, address: u16) -> u8 {
+        self.watch_a12(address);
+        self.chr[self.chr_offset(address)]
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        self.watch_a12(address);
+        if self.chr_is_ram {
+            let offset = self.chr_offset(address);
+            self.chr[offset] = value;
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn irq_pending(&self) -> bool {
+        self.irq_pending
+    }
+
+    // for a PPU that doesn't drive its pattern fetches through the mapper; one that does
+    // already clocks the counter on A12 and must not call this as well
+    fn notify_scanline(&mut self) {
+        self.clock_irq_counter();
+    }
+
+    fn cpu_clock(&mut self) {
+        self.cycle += 1;
+    }
+
+    fn save_ram(&self) -> Option<&[u8]> {
+        if self.battery { Some(&self.prg_ram) } else { None }
+    }
+
+    fn save_ram_mut(&mut self) -> Option<&mut [u8]> {
+        if self.battery { Some(&mut self.prg_ram) } else { None }
+    }
+}
@@ -0,0 +1,451 @@
+use crate::cartridge::{Mirroring, Rom};
+use crate::mapper::Mapper;
+
+const PRG_BANK_SIZE: usize = 0x2000;
+const PRG_RAM_SIZE: usize = 0x10000; // the most the board can address; iNES doesn't say how much is fitted
+const CHR_RAM_SIZE: usize = 0x2000;
+const EXRAM_SIZE: usize = 0x0400;
+
+// ppu reads into a scanline, counted from the nametable fetch of its third tile (the first
+// one after the three identical reads the scanline is detected by)
+const BACKGROUND_FETCHES: u16 = 128; // tiles 2-33, four fetches each
+const SPRITE_FETCHES_END: u16 = 160; // eight sprites: two garbage nametable fetches, two pattern
+const PREFETCH_END: u16 = 168; // tiles 0 and 1 of the next scanline
+
+// the cpu stops seeing ppu reads this many cycles into vblank
+const IDLE_CYCLES: u8 = 3;
+
+const SCREEN_LINES: u8 = 240;
+
+// mapper 5
+//
+// $5100 PRG mode, $5101 CHR mode, $5102/$5103 PRG-RAM write protect ($02/$01 to write)
+// $5104 ExRAM mode: nametable, extended attributes, cpu RAM, cpu ROM
+// $5105 nametable per quarter: VRAM page 0, page 1, ExRAM, fill; $5106/$5107 fill tile/attribute
+// $5113-$5117 PRG banks, 8 KiB units, bit 7 selecting ROM over RAM
+// $5120-$5127 CHR set A (sprites), $5128-$512B set B (background of 8x16 sprite games)
+// $5130 upper CHR bank bits
+// $5200-$5202 vertical split control, scroll and CHR bank
+// $5203 IRQ scanline, $5204 IRQ enable / status, $5205/$5206 8x8 multiplier
+// $5C00-$5FFF ExRAM
+pub struct Mmc5 {
+    prg_rom: Vec<u8>,
+    prg_ram: Vec<u8>,
+    chr: Vec<u8>,
+    chr_is_ram: bool,
+    exram: [u8; EXRAM_SIZE],
+    battery: bool,
+
+    prg_mode: u8,
+    chr_mode: u8,
+    prg_ram_protect: [u8; 2],
+    exram_mode: u8,
+    nametables: u8,
+    fill_tile: u8,
+    fill_attribute: u8,
+    prg_banks: [u8; 5], // $5113-$5117
+    chr_banks: [u16; 12],
+    chr_upper: u8,
+    last_chr_set_b: bool, // set B was written last, so it is used outside of 8x16 rendering
+
+    sprite_8x16: bool,
+    rendering: bool,
+
+    split_control: u8,
+    split_scroll: u8,
+    split_bank: u8,
+    split_y: u8,
+    split_tile: bool, // the tile being fetched is in the split region
+
+    irq_scanline: u8,
+    irq_enabled: bool,
+    irq_pending: bool,
+    in_frame: bool,
+    scanline: u8,
+
+    // scanline detection from the ppu's fetch pattern
+    last_ppu_address: Option<u16>,
+    repeated_reads: u8,
+    fetch_index: u16,
+    idle_cycles: u8,
+    ext_attribute: u8, // ExRAM byte of the tile being fetched in extended attribute mode
+
+    multiplicand: u8,
+    multiplier: u8,
+}
+
+enum PrgTarget {
+    Rom(usize),
+    Ram(usize),
+}
+
+impl Mmc5 {
+    pub fn new(rom: Rom) -> Self {
+        let chr_is_ram = rom.chr_rom.is_empty();
+        Mmc5 {
+            prg_rom: rom.prg_rom,
+            prg_ram: vec![0; PRG_RAM_SIZE],
+            chr: if chr_is_ram { vec![0; CHR_RAM_SIZE] } else { rom.chr_rom },
+            chr_is_ram,
+            exram: [0; EXRAM_SIZE],
+            battery: rom.battery,
+            prg_mode: 3,
+            chr_mode: 3,
+            prg_ram_protect: [0; 2],
+            exram_mode: 0,
+            nametables: 0,
+            fill_tile: 0,
+            fill_attribute: 0,
+            prg_banks: [0, 0, 0, 0, 0xFF],
+            chr_banks: [0; 12],
+            chr_upper: 0,
+            last_chr_set_b: false,
+            sprite_8x16: false,
+            rendering: false,
+            split_control: 0,
+            split_scroll: 0,
+            split_bank: 0,
+            split_y: 0,
+            split_tile: false,
+            irq_scanline: 0,
+            irq_enabled: false,
+            irq_pending: false,
+            in_frame: false,
+            scanline: 0,
+            last_ppu_address: None,
+            repeated_reads: 0,
+            fetch_index: 0,
+            idle_cycles: 0,
+            ext_attribute: 0,
+            multiplicand: 0xFF,
+            multiplier: 0xFF,
+        }
+    }
+
+    pub fn with_prg_ram_size(mut self, size: usize) -> Self {
+        self.prg_ram = vec![0; size.max(PRG_BANK_SIZE)];
+        self
+    }
+
+    fn prg_ram_writable(&self) -> bool {
+        self.prg_ram_protect == [0x02, 0x01]
+    }
+
+    fn prg_target(&self, address: u16) -> PrgTarget {
+        if address < 0x8000 {
+            let bank = (self.prg_banks[0] & 0x07) as usize;
+            return PrgTarget::Ram((bank * PRG_BANK_SIZE + (address as usize & 0x1FFF)) % self.prg_ram.len());
+        }
+
+        // the register for this 8 KiB slot, and how many slots it covers
+        let slot = ((address - 0x8000) >> 13) as usize;
+        let (register, banks) = match (self.prg_mode, slot) {
+            (0, _) => (4, 4),
+            (1, 0 | 1) | (2, 0 | 1) => (2, 2),
+            (1, _) => (4, 2),
+            (2, 2) => (3, 1),
+            (2, _) => (4, 1),
+            _ => (1 + slot, 1),
+        };
+        let value = self.prg_banks[register];
+        let bank = (value & 0x7F) as usize & !(banks - 1) | (slot & (banks - 1));
+
+        // $5117 always maps ROM
+        if register == 4 || value & 0x80 != 0 {
+            PrgTarget::Rom((bank * PRG_BANK_SIZE + (address as usize & 0x1FFF)) % self.prg_rom.len())
+        } else {
+            PrgTarget::Ram(((bank & 0x07) * PRG_BANK_SIZE + (address as usize & 0x1FFF)) % self.prg_ram.len())
+        }
+    }
+
+    // the ppu read about to happen is a background fetch for tile (x, on the next scanline)
+    fn background_tile(&self) -> Option<(u8, bool)> {
+        match self.fetch_index {
+            index if index < BACKGROUND_FETCHES => Some((((index / 4 + 2) & 0x1F) as u8, false)),
+            index if (SPRITE_FETCHES_END..PREFETCH_END).contains(&index) => {
+                Some((((index - SPRITE_FETCHES_END) / 4) as u8, true))
+            }
+            _ => None,
+        }
+    }
+
+    fn sprite_fetch(&self) -> bool {
+        (BACKGROUND_FETCHES..SPRITE_FETCHES_END).contains(&self.fetch_index)
+    }
+
+    fn split_active(&self, tile_x: u8) -> bool {
+        if self.split_control & 0x80 == 0 || self.exram_mode > 1 {
+            return false;
+        }
+        let threshold = self.split_control & 0x1F;
+        if self.split_control & 0x40 == 0 { tile_x < threshold } else { tile_x >= threshold }
+    }
+
+    fn split_line(&self, next_line: bool) -> u8 {
+        let y = self.split_y.wrapping_add(next_line as u8);
+        if y >= SCREEN_LINES { y - SCREEN_LINES } else { y }
+    }
+
+    // every ppu read passes through here: three reads of one address in a row mark the start
+    // of a scanline
+    fn note_ppu_read(&mut self, address: u16) {
+        self.idle_cycles = 0;
+        if self.last_ppu_address == Some(address) {
+            self.repeated_reads = self.repeated_reads.saturating_add(1);
+            if self.repeated_reads == 2 {
+                self.detect_scanline();
+            }
+        } else {
+            self.last_ppu_address = Some(address);
+            self.repeated_reads = 0;
+        }
+    }
+
+    fn detect_scanline(&mut self) {
+        if self.in_frame {
+            self.scanline = self.scanline.wrapping_add(1);
+            self.split_y = self.split_line(true);
+            if self.scanline == self.irq_scanline {
+                self.irq_pending = true;
+            }
+        } else {
+            self.in_frame = true;
+            self.scanline = 0;
+            self.split_y = self.split_scroll;
+            self.irq_pending = false;
+        }
+        self.fetch_index = 0;
+    }
+
+    fn leave_frame(&mut self) {
+        self.in_frame = false;
+        self.last_ppu_address = None;
+        self.repeated_reads = 0;
+    }
+
+    fn chr_offset(&self, address: u16) -> usize {
+        let address = address as usize & 0x1FFF;
+        // background fetches of an 8x16 sprite game use set B, everything else set A; outside
+        // of rendering whichever set was written last is used
+        let set_b = if self.in_frame && self.rendering && self.sprite_8x16 {
+            !self.sprite_fetch()
+        } else {
+            self.last_chr_set_b
+        };
+
+        let (bank, size) = match (self.chr_mode, set_b) {
+            (0, false) => (self.chr_banks[7], 0x2000),
+            (1, false) => (self.chr_banks[3 + 4 * (address >> 12)], 0x1000),
+            (2, false) => (self.chr_banks[1 + 2 * (address >> 11)], 0x0800),
+            (_, false) => (self.chr_banks[address >> 10], 0x0400),
+            // set B is four 1 KiB registers, repeated in both pattern tables
+            (0, true) => (self.chr_banks[11], 0x2000),
+            (1, true) => (self.chr_banks[11], 0x1000),
+            (2, true) => (self.chr_banks[9 + 2 * ((address >> 11) & 1)], 0x0800),
+            (_, true) => (self.chr_banks[8 + ((address >> 10) & 0x03)], 0x0400),
+        };
+        (bank as usize * size + (address & (size - 1))) % self.chr.len()
+    }
+
+    fn nametable_source(&self, address: u16, vram: &[u8]) -> u8 {
+        let offset = (address & 0x03FF) as usize;
+        match (self.nametables >> (((address >> 10) & 0x03) * 2)) & 0x03 {
+            0 => vram[offset],
+            1 => vram[0x0400 | offset],
+            2 if self.exram_mode <= 1 => self.exram[offset],
+            2 => 0,
+            _ if offset >= 0x03C0 => self.fill_attribute * 0x55,
+            _ => self.fill_tile,
+        }
+    }
+}
+
+impl Mapper for Mmc5 {
+    fn cpu_peek(&self, address: u16) -> Option<u8> {
+        match address {
+            0x5204 => Some((self.irq_pending as u8) << 7 | (self.in_frame as u8) << 6),
+            0x5205 => Some((self.multiplicand as u16 * self.multiplier as u16) as u8),
+            0x5206 => Some(((self.multiplicand as u16 * self.multiplier as u16) >> 8) as u8),
+            0x5C00..=0x5FFF if self.exram_mode >= 2 => Some(self.exram[(address & 0x03FF) as usize]),
+            0x6000..=0xFFFF => Some(match self.prg_target(address) {
+                PrgTarget::Rom(offset) => self.prg_rom[offset],
+                PrgTarget::Ram(offset) => self.prg_ram[offset],
+            }),
+            _ => None,
+        }
+    }
+
+    fn cpu_read(&mut self, address: u16) -> Option<u8> {
+        let value = self.cpu_peek(address);
+        match address {
+            0x5204 => self.irq_pending = false,
+            // fetching the NMI vector means vblank has started
+            0xFFFA | 0xFFFB => self.leave_frame(),
+            _ => {}
+        }
+        value
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) {
+        match address {
+            0x5100 => self.prg_mode = value & 0x03,
+            0x5101 => self.chr_mode = value & 0x03,
+            0x5102 => self.prg_ram_protect[0] = value & 0x03,
+            0x5103 => self.prg_ram_protect[1] = value & 0x03,
+            0x5104 => self.exram_mode = value & 0x03,
+            0x5105 => self.nametables = value,
+            0x5106 => self.fill_tile = value,
+            0x5107 => self.fill_attribute = value & 0x03,
+            0x5113..=0x5117 => self.prg_banks[(address - 0x5113) as usize] = value,
+            0x5120..=0x512B => {
+                let register = (address - 0x5120) as usize;
+                self.chr_banks[register] = value as u16 | (self.chr_upper as u16) << 8;
+                self.last_chr_set_b = register >= 8;
+            }
+            0x5130 => self.chr_upper = value & 0x03,
+            0x5200 => self.split_control = value,
+            0x5201 => self.split_scroll = value,
+            0x5202 => self.split_bank = value,
+            0x5203 => self.irq_scanline = value,
+            0x5204 => self.irq_enabled = value & 0x80 != 0,
+            0x5205 => self.multiplicand = value,
+            0x5206 => self.multiplier = value,
+            0x5C00..=0x5FFF => {
+                // in the nametable modes the cpu can only write while the ppu is rendering
+                // and zeroes get written otherwise
+                let offset = (address & 0x03FF) as usize;
+                match self.exram_mode {
+                    0 | 1 => self.exram[offset] = if self.in_frame { value } else { 0 },
+                    2 => self.exram[offset] = value,
+                    _ => {}
+                }
+            }
+            0x6000..=0xFFFF if self.prg_ram_writable() => {
+                if let PrgTarget::Ram(offset) = self.prg_target(address) {
+                    self.prg_ram[offset] = value;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn ppu_read(&mut self, address: u16) -> u8 {
+        self.note_ppu_read(address);
+        let background = if self.in_frame { self.background_tile() } else { None };
+
+        let offset = match background {
+            // the split region has its own 4 KiB bank and vertical scroll
+            Some((_, next_line)) if self.split_tile => {
+                let fine_y = (self.split_line(next_line) & 0x07) as usize;
+                self.split_bank as usize * 0x1000 + ((address as usize & 0x0FF8) | fine_y)
+            }
+            // extended attributes pick a 4 KiB bank per tile
+            Some(_) if self.exram_mode == 1 => {
+                let bank = (self.ext_attribute & 0x3F) as usize | (self.chr_upper as usize) << 6;
+                bank * 0x1000 + (address as usize & 0x0FFF)
+            }
+            _ => self.chr_offset(address),
+        };
+        self.fetch_index = self.fetch_index.saturating_add(1);
+        self.chr[offset % self.chr.len()]
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let offset = self.chr_offset(address);
+            self.chr[offset] = value;
+        }
+    }
+
+    // closest fixed arrangement of $5105, for anything that doesn't go through nametable_read
+    fn mirroring(&self) -> Mirroring {
+        match self.nametables {
+            0x44 => Mirroring::VERTICAL,
+            0x50 => Mirroring::HORIZONTAL,
+            0x55 => Mirroring::SINGLE_SCREEN_UPPER,
+            0x00 => Mirroring::SINGLE_SCREEN_LOWER,
+            _ => Mirroring::FOUR_SCREEN,
+        }
+    }
+
+    fn nametable_read(&mut self, address: u16, vram: &[u8]) -> u8 {
+        self.note_ppu_read(address);
+        let fetch = self.fetch_index;
+        let background = if self.in_frame { self.background_tile() } else { None };
+        self.fetch_index = self.fetch_index.saturating_add(1);
+
+        let Some((tile_x, next_line)) = background else {
+            return self.nametable_source(address, vram);
+        };
+        let nametable_fetch = fetch.is_multiple_of(4);
+        if nametable_fetch {
+            self.split_tile = self.split_active(tile_x);
+        }
+
+        if self.split_tile {
+            // the split region reads its own 32x30 tile screen from ExRAM
+            let line = self.split_line(next_line) as usize;
+            let tile_x = tile_x as usize;
+            return if nametable_fetch {
+                self.exram[(line / 8) * 32 + tile_x]
+            } else {
+                let attribute = self.exram[0x03C0 + (line / 32) * 8 + tile_x / 4];
+                let shift = ((line & 0x10) >> 2) | (tile_x & 0x02);
+                ((attribute >> shift) & 0x03) * 0x55
+            };
+        }
+
+        if self.exram_mode == 1 {
+            if nametable_fetch {
+                self.ext_attribute = self.exram[(address & 0x03FF) as usize];
+            } else {
+                return (self.ext_attribute >> 6) * 0x55;
+            }
+        }
+        self.nametable_source(address, vram)
+    }
+
+    fn nametable_write(&mut self, address: u16, value: u8, vram: &mut [u8]) {
+        let offset = (address & 0x03FF) as usize;
+        match (self.nametables >> (((address >> 10) & 0x03) * 2)) & 0x03 {
+            0 => vram[offset] = value,
+            1 => vram[0x0400 | offset] = value,
+            2 if self.exram_mode <= 1 => self.exram[offset] = value,
+            _ => {}
+        }
+    }
+
+    fn ppu_register_write(&mut self, address: u16, value: u8) {
+        match address {
+            0x2000 => self.sprite_8x16 = value & 0x20 != 0,
+            0x2001 => {
+                self.rendering = value & 0x18 != 0;
+                if !self.rendering {
+                    self.leave_frame();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn irq_pending(&self) -> bool {
+        self.irq_pending && self.irq_enabled
+    }
+
+    fn cpu_clock(&mut self) {
+        if self.in_frame {
+            self.idle_cycles += 1;
+            if self.idle_cycles >= IDLE_CYCLES {
+                self.leave_frame();
+            }
+        }
+    }
+
+    fn save_ram(&self) -> Option<&[u8]> {
+        if self.battery { Some(&self.prg_ram) } else { None }
+    }
+
+    fn save_ram_mut(&mut self) -> Option<&mut [u8]> {
+        if self.battery { Some(&mut self.prg_ram) } else { None }
+    }
+}
@@ -0,0 +1,143 @@
+use crate::cartridge::{HeaderFormat, Mirroring, Rom};
+use crate::error::{EmulatorError, InvalidRomKind};
+
+mod bandai;
+mod discrete;
+mod eeprom;
+mod fme7;
+mod mmc1;
+mod mmc2;
+mod mmc3;
+mod mmc5;
+mod nrom;
+mod vrc;
+
+pub use bandai::{Bandai, BandaiBoard};
+pub use discrete::{Discrete, DiscreteBoard};
+pub use fme7::Fme7;
+pub use mmc1::Mmc1;
+pub use mmc2::Mmc2;
+pub use mmc3::{Mmc3, Mmc3Revision};
+pub use mmc5::Mmc5;
+pub use nrom::Nrom;
+pub use vrc::{Vrc, VrcVariant};
+
+// a cartridge board as seen from the CPU ($4020-$FFFF) and PPU ($0000-$1FFF) buses
+pub trait Mapper {
+    // reads without side effects; None leaves the CPU data bus floating (open bus)
+    fn cpu_peek(&self, address: u16) -> Option<u8>;
+
+    // boards whose registers react to reads override this
+    fn cpu_read(&mut self, address: u16) -> Option<u8> {
+        self.cpu_peek(address)
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8);
+
+    fn ppu_read(&mut self, address: u16) -> u8;
+    fn ppu_write(&mut self, address: u16, value: u8);
+
+    fn mirroring(&self) -> Mirroring;
+
+    // nametable fetches ($2000-$2FFF) from the console's 2 KiB of VRAM, arranged per
+    // mirroring(); boards with their own nametable RAM or logic override these
+    fn nametable_read(&mut self, address: u16, vram: &[u8]) -> u8 {
+        vram[nametable_offset(self.mirroring(), address)]
+    }
+
+    fn nametable_write(&mut self, address: u16, value: u8, vram: &mut [u8]) {
+        vram[nametable_offset(self.mirroring(), address)] = value;
+    }
+
+    // cpu writes to the PPU registers ($2000-$2007), for boards that snoop on them
+    fn ppu_register_write(&mut self, _address: u16, _value: u8) {}
+
+    // state of the cartridge's IRQ output
+    fn irq_pending(&self) -> bool {
+        false
+    }
+
+    // called once per rendered scanline, for boards that count them
+    fn notify_scanline(&mut self) {}
+
+    // called once per CPU cycle, for boards with cycle-counting IRQs
+    fn cpu_clock(&mut self) {}
+
+    // battery-backed memory to persist between runs, if the board has any
+    fn save_ram(&self) -> Option<&[u8]> {
+        None
+    }
+
+    // the same memory, for restoring a save
+    fn save_ram_mut(&mut self) -> Option<&mut [u8]> {
+        None
+    }
+}
+
+// where a nametable address lands in VRAM; four-screen needs the 4 KiB those boards carry
+pub fn nametable_offset(mirroring: Mirroring, address: u16) -> usize {
+    let address = address as usize;
+    match mirroring {
+        Mirroring::VERTICAL => address & 0x07FF,
+        Mirroring::HORIZONTAL => ((address >> 1) & 0x0400) | (address & 0x03FF),
+        Mirroring::SINGLE_SCREEN_LOWER => address & 0x03FF,
+        Mirroring::SINGLE_SCREEN_UPPER => 0x0400 | (address & 0x03FF),
+        Mirroring::FOUR_SCREEN => address & 0x0FFF,
+    }
+}
+
+// PRG-RAM (volatile and battery-backed together) a NES 2.0 header asks for; iNES doesn't say
+fn prg_ram_size(rom: &Rom) -> Option<usize> {
+    let header = &rom.header;
+    let size = header.prg_ram_size + header.prg_nvram_size;
+    (header.format == HeaderFormat::Nes2 && size > 0).then_some(size)
+}
+
+// builds the board for the ROM's mapper number, using the NES 2.0 submapper where it matters
+pub fn from_rom(rom: Rom) -> Result<Box<dyn Mapper>, EmulatorError> {
+    if rom.prg_rom.is_empty() {
+        return Err(EmulatorError::InvalidRom(InvalidRomKind::MissingPrgRom));
+    }
+    let submapper = rom.header.submapper;
+    // submapper 1 of the discrete boards has no bus conflicts and 2 has them
+    let bus_conflicts = submapper == 2;
+    match rom.mapper {
+        0 => Ok(Box::new(Nrom::new(rom))),
+        1 => {
+            let size = prg_ram_size(&rom);
+            let mmc1 = Mmc1::new(rom);
+            Ok(Box::new(match size {
+                Some(size) => mmc1.with_prg_ram_size(size),
+                None => mmc1,
+            }))
+        }
+        2 => Ok(Box::new(Discrete::new(DiscreteBoard::UxRom, rom).with_bus_conflicts(bus_conflicts))),
+        3 => Ok(Box::new(Discrete::new(DiscreteBoard::CnRom, rom).with_bus_conflicts(bus_conflicts))),
+        // submapper 4 is the MMC3A
+        4 if submapper == 4 => Ok(Box::new(Mmc3::new(rom).with_revision(Mmc3Revision::A))),
+        4 => Ok(Box::new(Mmc3::new(rom))),
+        5 => {
+            let size = prg_ram_size(&rom);
+            let mmc5 = Mmc5::new(rom);
+            Ok(Box::new(match size {
+                Some(size) => mmc5.with_prg_ram_size(size),
+                None => mmc5,
+            }))
+        }
+        7 => Ok(Box::new(Discrete::new(DiscreteBoard::AxRom, rom).with_bus_conflicts(bus_conflicts))),
+        9 => Ok(Box::new(Mmc2::new(rom))),
+        10 => Ok(Box::new(Mmc2::mmc4(rom))),
+        11 => Ok(Box::new(Discrete::new(DiscreteBoard::ColorDreams, rom))),
+        16 => Ok(Box::new(Bandai::new(BandaiBoard::Mapper16, rom))),
+        21 | 22 | 23 | 25 => {
+            let variants = VrcVariant::for_mapper(rom.mapper, submapper);
+            Ok(Box::new(Vrc::new(rom, variants)))
+        }
+        34 => Ok(Box::new(Discrete::new(DiscreteBoard::BnRom, rom))),
+        66 => Ok(Box::new(Discrete::new(DiscreteBoard::GxRom, rom))),
+        69 => Ok(Box::new(Fme7::new(rom))),
+        153 => Ok(Box::new(Bandai::new(BandaiBoard::Mapper153, rom))),
+        159 => Ok(Box::new(Bandai::new(BandaiBoard::Mapper159, rom))),
+        mapper => Err(EmulatorError::UnsupportedMapper(mapper)),
+    }
+}
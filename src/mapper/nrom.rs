@@ -0,0 +1,73 @@
+use crate::cartridge::{Mirroring, Rom};
+use crate::mapper::Mapper;
+
+const PRG_RAM_SIZE: usize = 0x2000;
+const CHR_RAM_SIZE: usize = 0x2000;
+
+// mapper 0: 16 or 32 KiB of PRG-ROM (16 KiB is mirrored at $C000), 8 KiB of CHR-ROM or CHR-RAM
+// and the 8 KiB of PRG-RAM Family Basic carts have at $6000
+pub struct Nrom {
+    prg_rom: Vec<u8>,
+    prg_ram: Vec<u8>,
+    chr: Vec<u8>,
+    chr_is_ram: bool,
+    mirroring: Mirroring,
+    battery: bool,
+}
+
+impl Nrom {
+    pub fn new(rom: Rom) -> Self {
+        let chr_is_ram = rom.chr_rom.is_empty();
+        Nrom {
+            prg_rom: rom.prg_rom,
+            prg_ram: vec![0; PRG_RAM_SIZE],
+            chr: if chr_is_ram { vec![0; CHR_RAM_SIZE] } else { rom.chr_rom },
+            chr_is_ram,
+            mirroring: rom.screen_mirroring,
+            battery: rom.battery,
+        }
+    }
+}
+
+impl Mapper for Nrom {
+    fn cpu_peek(&self, address: u16) -> Option<u8> {
+        match address {
+            0x6000..=0x7FFF => Some(self.prg_ram[(address - 0x6000) as usize]),
+            0x8000..=0xFFFF => {
+                let offset = (address - 0x8000) as usize % self.prg_rom.len();
+                Some(self.prg_rom[offset])
+            }
+            _ => None,
+        }
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) {
+        // no registers, writes to ROM have no effect
+        if let 0x6000..=0x7FFF = address {
+            self.prg_ram[(address - 0x6000) as usize] = value;
+        }
+    }
+
+    fn ppu_read(&mut self, address: u16) -> u8 {
+        self.chr[(address & 0x1FFF) as usize % self.chr.len()]
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let offset = (address & 0x1FFF) as usize % self.chr.len();
+            self.chr[offset] = value;
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn save_ram(&self) -> Option<&[u8]> {
+        if self.battery { Some(&self.prg_ram) } else { None }
+    }
+
+    fn save_ram_mut(&mut self) -> Option<&mut [u8]> {
+        if self.battery { Some(&mut self.prg_ram) } else { None }
+    }
+}
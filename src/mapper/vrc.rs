@@ -0,0 +1,270 @@
+use crate::cartridge::{Mirroring, Rom};
+use crate::mapper::Mapper;
+
+const PRG_BANK_SIZE: usize = 0x2000;
+const CHR_BANK_SIZE: usize = 0x0400;
+const PRG_RAM_SIZE: usize = 0x2000;
+const CHR_RAM_SIZE: usize = 0x2000;
+
+// ppu cycles per scanline, which the VRC4 prescaler counts down in steps of 3 per cpu cycle
+const PRESCALER_PERIOD: i16 = 341;
+
+// the boards differ in which two cpu address lines select among the four registers at each
+// $x000 base, given as (line for register bit 0, line for register bit 1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VrcVariant {
+    Vrc2a, // mapper 22
+    Vrc2b, // mapper 23
+    Vrc2c, // mapper 25
+    Vrc4a, // mapper 21
+    Vrc4b, // mapper 25
+    Vrc4c, // mapper 21
+    Vrc4d, // mapper 25
+    Vrc4e, // mapper 23
+    Vrc4f, // mapper 23
+}
+
+impl VrcVariant {
+    fn address_lines(self) -> (u16, u16) {
+        match self {
+            VrcVariant::Vrc2a | VrcVariant::Vrc2c | VrcVariant::Vrc4b => (1 << 1, 1 << 0),
+            VrcVariant::Vrc2b | VrcVariant::Vrc4f => (1 << 0, 1 << 1),
+            VrcVariant::Vrc4a => (1 << 1, 1 << 2),
+            VrcVariant::Vrc4c => (1 << 6, 1 << 7),
+            VrcVariant::Vrc4d => (1 << 3, 1 << 2),
+            VrcVariant::Vrc4e => (1 << 2, 1 << 3),
+        }
+    }
+
+    fn is_vrc4(self) -> bool {
+        !matches!(self, VrcVariant::Vrc2a | VrcVariant::Vrc2b | VrcVariant::Vrc2c)
+    }
+
+    // the variants a mapper number can stand for; NES 2.0 submappers 1 and 2 pick one of the
+    // VRC4s and 3 the VRC2, 0 (and iNES) leaves every candidate decoded
+    pub fn for_mapper(mapper: u16, submapper: u8) -> &'static [VrcVariant] {
+        match (mapper, submapper) {
+            (21, 1) => &[VrcVariant::Vrc4a],
+            (21, 2) => &[VrcVariant::Vrc4c],
+            (21, _) => &[VrcVariant::Vrc4a, VrcVariant::Vrc4c],
+            (22, _) => &[VrcVariant::Vrc2a],
+            (23, 1) => &[VrcVariant::Vrc4f],
+            (23, 2) => &[VrcVariant::Vrc4e],
+            (23, 3) => &[VrcVariant::Vrc2b],
+            (23, _) => &[VrcVariant::Vrc2b, VrcVariant::Vrc4e, VrcVariant::Vrc4f],
+            (25, 1) => &[VrcVariant::Vrc4b],
+            (25, 2) => &[VrcVariant::Vrc4d],
+            (25, 3) => &[VrcVariant::Vrc2c],
+            (25, _) => &[VrcVariant::Vrc2c, VrcVariant::Vrc4b, VrcVariant::Vrc4d],
+            _ => &[],
+        }
+    }
+}
+
+// mappers 21, 22, 23 and 25: Konami VRC2 and VRC4
+//
+// $8000 PRG bank at $8000 (or $C000 in swap mode), $A000 PRG bank at $A000
+// $9000 mirroring, $9002 PRG swap mode (VRC4)
+// $B000-$E003 CHR banks 0-7, each written a nibble at a time (low then high register)
+// $F000/$F001 IRQ latch low/high nibble, $F002 IRQ control, $F003 IRQ acknowledge (VRC4)
+pub struct Vrc {
+    vrc4: bool,
+    chr_shift: u8, // VRC2a drops the low bit of its CHR banks
+    low_line: u16,
+    high_line: u16,
+    prg_rom: Vec<u8>,
+    prg_ram: Vec<u8>,
+    chr: Vec<u8>,
+    chr_is_ram: bool,
+    battery: bool,
+
+    prg_banks: [u8; 2],
+    prg_swap: bool,
+    chr_banks: [u16; 8],
+    mirroring: Mirroring,
+
+    irq_latch: u8,
+    irq_counter: u8,
+    irq_prescaler: i16,
+    irq_enable_after_ack: bool,
+    irq_enabled: bool,
+    irq_cycle_mode: bool,
+    irq_pending: bool,
+}
+
+impl Vrc {
+    // a board that decodes the address lines of every variant given, which is how the ones
+    // sharing an iNES mapper number can all run without knowing which it is
+    pub fn new(rom: Rom, variants: &[VrcVariant]) -> Self {
+        let (low_line, high_line) = variants.iter().fold((0, 0), |(low, high), variant| {
+            let (l, h) = variant.address_lines();
+            (low | l, high | h)
+        });
+        let chr_is_ram = rom.chr_rom.is_empty();
+        Vrc {
+            vrc4: variants.iter().any(|variant| variant.is_vrc4()),
+            chr_shift: if variants == [VrcVariant::Vrc2a] { 1 } else { 0 },
+            low_line,
+            high_line,
+            prg_rom: rom.prg_rom,
+            prg_ram: vec![0; PRG_RAM_SIZE],
+            chr: if chr_is_ram { vec![0; CHR_RAM_SIZE] } else { rom.chr_rom },
+            chr_is_ram,
+            battery: rom.battery,
+            prg_banks: [0, 0],
+            prg_swap: false,
+            chr_banks: [0; 8],
+            mirroring: rom.screen_mirroring,
+            irq_latch: 0,
+            irq_counter: 0,
+            irq_prescaler: PRESCALER_PERIOD,
+            irq_enable_after_ack: false,
+            irq_enabled: false,
+            irq_cycle_mode: false,
+            irq_pending: false,
+        }
+    }
+
+    // folds the board's address wiring down to $x000-$x003
+    fn register(&self, address: u16) -> u16 {
+        let low = (address & self.low_line != 0) as u16;
+        let high = (address & self.high_line != 0) as u16;
+        (address & 0xF000) | (high << 1) | low
+    }
+
+    fn prg_offset(&self, address: u16) -> usize {
+        let banks = self.prg_rom.len() / PRG_BANK_SIZE;
+        let second_last = banks.saturating_sub(2);
+        let bank = match ((address >> 13) & 0x03, self.prg_swap) {
+            (0, false) | (2, true) => self.prg_banks[0] as usize,
+            (0, true) | (2, false) => second_last,
+            (1, _) => self.prg_banks[1] as usize,
+            _ => banks.saturating_sub(1),
+        };
+        (bank * PRG_BANK_SIZE + (address as usize & (PRG_BANK_SIZE - 1))) % self.prg_rom.len()
+    }
+
+    fn chr_offset(&self, address: u16) -> usize {
+        let bank = (self.chr_banks[((address >> 10) & 0x07) as usize] >> self.chr_shift) as usize;
+        (bank * CHR_BANK_SIZE + (address as usize & (CHR_BANK_SIZE - 1))) % self.chr.len()
+    }
+
+    fn write_chr_nibble(&mut self, register: u16, value: u8) {
+        // $B000/$B001 is bank 0, $B002/$B003 bank 1, ... $E002/$E003 bank 7
+        let bank = (((register >> 12) - 0xB) * 2 + ((register >> 1) & 1)) as usize;
+        let value = value as u16;
+        self.chr_banks[bank] = if register & 1 == 0 {
+            (self.chr_banks[bank] & 0x1F0) | (value & 0x0F)
+        } else {
+            // the VRC4 has a fifth high bit, bank bit 8
+            let high = if self.vrc4 { value & 0x1F } else { value & 0x0F };
+            (self.chr_banks[bank] & 0x0F) | (high << 4)
+        };
+    }
+
+    fn clock_irq_counter(&mut self) {
+        if self.irq_counter == 0xFF {
+            self.irq_counter = self.irq_latch;
+            self.irq_pending = true;
+        } else {
+            self.irq_counter += 1;
+        }
+    }
+}
+
+impl Mapper for Vrc {
+    fn cpu_peek(&self, address: u16) -> Option<u8> {
+        match address {
+            0x6000..=0x7FFF => Some(self.prg_ram[(address - 0x6000) as usize]),
+            0x8000..=0xFFFF => Some(self.prg_rom[self.prg_offset(address)]),
+            _ => None,
+        }
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) {
+        if let 0x6000..=0x7FFF = address {
+            self.prg_ram[(address - 0x6000) as usize] = value;
+            return;
+        }
+
+        let register = self.register(address);
+        match register {
+            0x8000..=0x8003 => self.prg_banks[0] = value & 0x1F,
+            0xA000..=0xA003 => self.prg_banks[1] = value & 0x1F,
+            0x9000..=0x9003 if !self.vrc4 => {
+                self.mirroring = if value & 1 == 0 { Mirroring::VERTICAL } else { Mirroring::HORIZONTAL };
+            }
+            0x9000 => {
+                self.mirroring = match value & 0x03 {
+                    0 => Mirroring::VERTICAL,
+                    1 => Mirroring::HORIZONTAL,
+                    2 => Mirroring::SINGLE_SCREEN_LOWER,
+                    _ => Mirroring::SINGLE_SCREEN_UPPER,
+                };
+            }
+            0x9002 => self.prg_swap = value & 0x02 != 0,
+            0xB000..=0xEFFF => self.write_chr_nibble(register, value),
+            0xF000 if self.vrc4 => self.irq_latch = (self.irq_latch & 0xF0) | (value & 0x0F),
+            0xF001 if self.vrc4 => self.irq_latch = (self.irq_latch & 0x0F) | (value << 4),
+            0xF002 if self.vrc4 => {
+                self.irq_enable_after_ack = value & 0x01 != 0;
+                self.irq_enabled = value & 0x02 != 0;
+                self.irq_cycle_mode = value & 0x04 != 0;
+                self.irq_pending = false;
+                if self.irq_enabled {
+                    self.irq_counter = self.irq_latch;
+                    self.irq_prescaler = PRESCALER_PERIOD;
+                }
+            }
+            0xF003 if self.vrc4 => {
+                self.irq_pending = false;
+                self.irq_enabled = self.irq_enable_after_ack;
+            }
+            _ => {}
+        }
+    }
+
+    fn ppu_read(&mut self, address: u16) -> u8 {
+        self.chr[self.chr_offset(address)]
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let offset = self.chr_offset(address);
+            self.chr[offset] = value;
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn irq_pending(&self) -> bool {
+        self.irq_pending
+    }
+
+    // in scanline mode the prescaler divides cpu cycles by 113 2/3 so the counter
+    // steps once per scanline without looking at the PPU
+    fn cpu_clock(&mut self) {
+        if !self.irq_enabled {
+            return;
+        }
+        if self.irq_cycle_mode {
+            self.clock_irq_counter();
+            return;
+        }
+        self.irq_prescaler -= 3;
+        if self.irq_prescaler <= 0 {
+            self.irq_prescaler += PRESCALER_PERIOD;
+            self.clock_irq_counter();
+        }
+    }
+
+    fn save_ram(&self) -> Option<&[u8]> {
+        if self.battery { Some(&self.prg_ram) } else { None }
+    }
+
+    fn save_ram_mut(&mut self) -> Option<&mut [u8]> {
+        if self.battery { Some(&mut self.prg_ram) } else { None }
+    }
+}
@@ -0,0 +1,41 @@
+use crate::cpu::Memory;
+
+// a plain 64 KiB address space with no mirroring or I/O, for tests and non-NES programs
+pub struct FlatMemory {
+    data: Box<[u8; 0x10000]>,
+}
+
+impl FlatMemory {
+    pub fn new() -> Self {
+        FlatMemory {
+            data: Box::new([0; 0x10000]),
+        }
+    }
+
+    // copies `bytes` into memory starting at `address`, wrapping at the top of the address space
+    pub fn load(&mut self, address: u16, bytes: &[u8]) {
+        for (i, byte) in bytes.iter().enumerate() {
+            self.data[address.wrapping_add(i as u16) as usize] = *byte;
+        }
+    }
+}
+
+impl Default for FlatMemory {
+    fn default() -> Self {
+        FlatMemory::new()
+    }
+}
+
+impl Memory for FlatMemory {
+    fn mem_read(&mut self, address: u16) -> u8 {
+        self.data[address as usize]
+    }
+
+    fn mem_write(&mut self, address: u16, value: u8) {
+        self.data[address as usize] = value;
+    }
+
+    fn peek(&self, address: u16) -> u8 {
+        self.data[address as usize]
+    }
+}
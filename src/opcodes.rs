@@ -1,6 +1,4 @@
-use crate::cpu::AddressingMode;
-use std::collections::HashMap;
-use lazy_static::lazy_static;
+use crate::cpu::{AddressingMode, CpuVariant};
 
 pub struct Instruction {
     pub code: u8,
@@ -8,203 +6,490 @@ pub struct Instruction {
     pub len: u8,
     pub mode: AddressingMode,
     pub cycles: u8,
+    pub unofficial: bool, // undocumented opcode, shown with a '*' in traces
 }
 
 impl Instruction {
-    fn new(code: u8, mnemonic: &'static str, len: u8, mode: AddressingMode, cycles: u8) -> Self {
+    const fn new(code: u8, mnemonic: &'static str, len: u8, mode: AddressingMode, cycles: u8) -> Self {
         Instruction {
             code,
             mnemonic,
             len,
             mode,
             cycles,
+            unofficial: false,
+        }
+    }
+
+    const fn unofficial(code: u8, mnemonic: &'static str, len: u8, mode: AddressingMode, cycles: u8) -> Self {
+        Instruction {
+            code,
+            mnemonic,
+            len,
+            mode,
+            cycles,
+            unofficial: true,
         }
     }
 }
 
-lazy_static! {
-    pub static ref CPU_INSTRUCTIONS: Vec<Instruction> = vec![
-        // --- System Functions ---
-        Instruction::new(0x00, "BRK", 1, AddressingMode::NonAddressing, 7),
-        Instruction::new(0xEA, "NOP", 1, AddressingMode::NonAddressing, 2),
-        Instruction::new(0x40, "RTI", 1, AddressingMode::NonAddressing, 6),
-
-        // --- Jumps & Subroutines ---
-        Instruction::new(0x4C, "JMP", 3, AddressingMode::Absolute, 3),
-        Instruction::new(0x6C, "JMP", 3, AddressingMode::NonAddressing, 5), // Indirect
-        Instruction::new(0x20, "JSR", 3, AddressingMode::Absolute, 6),
-        Instruction::new(0x60, "RTS", 1, AddressingMode::NonAddressing, 6),
-
-        // --- Load/Store Operations ---
-        Instruction::new(0xA9, "LDA", 2, AddressingMode::Immediate, 2),
-        Instruction::new(0xA5, "LDA", 2, AddressingMode::ZeroPage, 3),
-        Instruction::new(0xB5, "LDA", 2, AddressingMode::ZeroPageX, 4),
-        Instruction::new(0xAD, "LDA", 3, AddressingMode::Absolute, 4),
-        Instruction::new(0xBD, "LDA", 3, AddressingMode::AbsoluteX, 4),
-        Instruction::new(0xB9, "LDA", 3, AddressingMode::AbsoluteY, 4),
-        Instruction::new(0xA1, "LDA", 2, AddressingMode::IndirectX, 6),
-        Instruction::new(0xB1, "LDA", 2, AddressingMode::IndirectY, 5),
-        Instruction::new(0xA2, "LDX", 2, AddressingMode::Immediate, 2),
-        Instruction::new(0xA6, "LDX", 2, AddressingMode::ZeroPage, 3),
-        Instruction::new(0xB6, "LDX", 2, AddressingMode::ZeroPageY, 4),
-        Instruction::new(0xAE, "LDX", 3, AddressingMode::Absolute, 4),
-        Instruction::new(0xBE, "LDX", 3, AddressingMode::AbsoluteY, 4),
-        Instruction::new(0xA0, "LDY", 2, AddressingMode::Immediate, 2),
-        Instruction::new(0xA4, "LDY", 2, AddressingMode::ZeroPage, 3),
-        Instruction::new(0xB4, "LDY", 2, AddressingMode::ZeroPageX, 4),
-        Instruction::new(0xAC, "LDY", 3, AddressingMode::Absolute, 4),
-        Instruction::new(0xBC, "LDY", 3, AddressingMode::AbsoluteX, 4),
-        Instruction::new(0x85, "STA", 2, AddressingMode::ZeroPage, 3),
-        Instruction::new(0x95, "STA", 2, AddressingMode::ZeroPageX, 4),
-        Instruction::new(0x8D, "STA", 3, AddressingMode::Absolute, 4),
-        Instruction::new(0x9D, "STA", 3, AddressingMode::AbsoluteX, 5),
-        Instruction::new(0x99, "STA", 3, AddressingMode::AbsoluteY, 5),
-        Instruction::new(0x81, "STA", 2, AddressingMode::IndirectX, 6),
-        Instruction::new(0x91, "STA", 2, AddressingMode::IndirectY, 6),
-        Instruction::new(0x86, "STX", 2, AddressingMode::ZeroPage, 3),
-        Instruction::new(0x96, "STX", 2, AddressingMode::ZeroPageY, 4),
-        Instruction::new(0x8E, "STX", 3, AddressingMode::Absolute, 4),
-        Instruction::new(0x84, "STY", 2, AddressingMode::ZeroPage, 3),
-        Instruction::new(0x94, "STY", 2, AddressingMode::ZeroPageX, 4),
-        Instruction::new(0x8C, "STY", 3, AddressingMode::Absolute, 4),
-
-        // --- Register Transfers ---
-        Instruction::new(0xAA, "TAX", 1, AddressingMode::NonAddressing, 2),
-        Instruction::new(0xA8, "TAY", 1, AddressingMode::NonAddressing, 2),
-        Instruction::new(0xBA, "TSX", 1, AddressingMode::NonAddressing, 2),
-        Instruction::new(0x8A, "TXA", 1, AddressingMode::NonAddressing, 2),
-        Instruction::new(0x9A, "TXS", 1, AddressingMode::NonAddressing, 2),
-        Instruction::new(0x98, "TYA", 1, AddressingMode::NonAddressing, 2),
-
-        // --- Stack Operations ---
-        Instruction::new(0x48, "PHA", 1, AddressingMode::NonAddressing, 3),
-        Instruction::new(0x08, "PHP", 1, AddressingMode::NonAddressing, 3),
-        Instruction::new(0x68, "PLA", 1, AddressingMode::NonAddressing, 4),
-        Instruction::new(0x28, "PLP", 1, AddressingMode::NonAddressing, 4),
-
-        // --- Logical ---
-        Instruction::new(0x29, "AND", 2, AddressingMode::Immediate, 2),
-        Instruction::new(0x25, "AND", 2, AddressingMode::ZeroPage, 3),
-        Instruction::new(0x35, "AND", 2, AddressingMode::ZeroPageX, 4),
-        Instruction::new(0x2D, "AND", 3, AddressingMode::Absolute, 4),
-        Instruction::new(0x3D, "AND", 3, AddressingMode::AbsoluteX, 4),
-        Instruction::new(0x39, "AND", 3, AddressingMode::AbsoluteY, 4),
-        Instruction::new(0x21, "AND", 2, AddressingMode::IndirectX, 6),
-        Instruction::new(0x31, "AND", 2, AddressingMode::IndirectY, 5),
-        Instruction::new(0x49, "EOR", 2, AddressingMode::Immediate, 2),
-        Instruction::new(0x45, "EOR", 2, AddressingMode::ZeroPage, 3),
-        Instruction::new(0x55, "EOR", 2, AddressingMode::ZeroPageX, 4),
-        Instruction::new(0x4D, "EOR", 3, AddressingMode::Absolute, 4),
-        Instruction::new(0x5D, "EOR", 3, AddressingMode::AbsoluteX, 4),
-        Instruction::new(0x59, "EOR", 3, AddressingMode::AbsoluteY, 4),
-        Instruction::new(0x41, "EOR", 2, AddressingMode::IndirectX, 6),
-        Instruction::new(0x51, "EOR", 2, AddressingMode::IndirectY, 5),
-        Instruction::new(0x09, "ORA", 2, AddressingMode::Immediate, 2),
-        Instruction::new(0x05, "ORA", 2, AddressingMode::ZeroPage, 3),
-        Instruction::new(0x15, "ORA", 2, AddressingMode::ZeroPageX, 4),
-        Instruction::new(0x0D, "ORA", 3, AddressingMode::Absolute, 4),
-        Instruction::new(0x1D, "ORA", 3, AddressingMode::AbsoluteX, 4),
-        Instruction::new(0x19, "ORA", 3, AddressingMode::AbsoluteY, 4),
-        Instruction::new(0x01, "ORA", 2, AddressingMode::IndirectX, 6),
-        Instruction::new(0x11, "ORA", 2, AddressingMode::IndirectY, 5),
-        Instruction::new(0x24, "BIT", 2, AddressingMode::ZeroPage, 3),
-        Instruction::new(0x2C, "BIT", 3, AddressingMode::Absolute, 4),
-
-        // --- Arithmetic ---
-        Instruction::new(0x69, "ADC", 2, AddressingMode::Immediate, 2),
-        Instruction::new(0x65, "ADC", 2, AddressingMode::ZeroPage, 3),
-        Instruction::new(0x75, "ADC", 2, AddressingMode::ZeroPageX, 4),
-        Instruction::new(0x6D, "ADC", 3, AddressingMode::Absolute, 4),
-        Instruction::new(0x7D, "ADC", 3, AddressingMode::AbsoluteX, 4),
-        Instruction::new(0x79, "ADC", 3, AddressingMode::AbsoluteY, 4),
-        Instruction::new(0x61, "ADC", 2, AddressingMode::IndirectX, 6),
-        Instruction::new(0x71, "ADC", 2, AddressingMode::IndirectY, 5),
-        Instruction::new(0xE9, "SBC", 2, AddressingMode::Immediate, 2),
-        Instruction::new(0xE5, "SBC", 2, AddressingMode::ZeroPage, 3),
-        Instruction::new(0xF5, "SBC", 2, AddressingMode::ZeroPageX, 4),
-        Instruction::new(0xED, "SBC", 3, AddressingMode::Absolute, 4),
-        Instruction::new(0xFD, "SBC", 3, AddressingMode::AbsoluteX, 4),
-        Instruction::new(0xF9, "SBC", 3, AddressingMode::AbsoluteY, 4),
-        Instruction::new(0xE1, "SBC", 2, AddressingMode::IndirectX, 6),
-        Instruction::new(0xF1, "SBC", 2, AddressingMode::IndirectY, 5),
-
-        // --- Comparisons ---
-        Instruction::new(0xC9, "CMP", 2, AddressingMode::Immediate, 2),
-        Instruction::new(0xC5, "CMP", 2, AddressingMode::ZeroPage, 3),
-        Instruction::new(0xD5, "CMP", 2, AddressingMode::ZeroPageX, 4),
-        Instruction::new(0xCD, "CMP", 3, AddressingMode::Absolute, 4),
-        Instruction::new(0xDD, "CMP", 3, AddressingMode::AbsoluteX, 4),
-        Instruction::new(0xD9, "CMP", 3, AddressingMode::AbsoluteY, 4),
-        Instruction::new(0xC1, "CMP", 2, AddressingMode::IndirectX, 6),
-        Instruction::new(0xD1, "CMP", 2, AddressingMode::IndirectY, 5),
-        Instruction::new(0xE0, "CPX", 2, AddressingMode::Immediate, 2),
-        Instruction::new(0xE4, "CPX", 2, AddressingMode::ZeroPage, 3),
-        Instruction::new(0xEC, "CPX", 3, AddressingMode::Absolute, 4),
-        Instruction::new(0xC0, "CPY", 2, AddressingMode::Immediate, 2),
-        Instruction::new(0xC4, "CPY", 2, AddressingMode::ZeroPage, 3),
-        Instruction::new(0xCC, "CPY", 3, AddressingMode::Absolute, 4),
-
-        // --- Increments & Decrements ---
-        Instruction::new(0xE6, "INC", 2, AddressingMode::ZeroPage, 5),
-        Instruction::new(0xF6, "INC", 2, AddressingMode::ZeroPageX, 6),
-        Instruction::new(0xEE, "INC", 3, AddressingMode::Absolute, 6),
-        Instruction::new(0xFE, "INC", 3, AddressingMode::AbsoluteX, 7),
-        Instruction::new(0xE8, "INX", 1, AddressingMode::NonAddressing, 2),
-        Instruction::new(0xC8, "INY", 1, AddressingMode::NonAddressing, 2),
-        Instruction::new(0xC6, "DEC", 2, AddressingMode::ZeroPage, 5),
-        Instruction::new(0xD6, "DEC", 2, AddressingMode::ZeroPageX, 6),
-        Instruction::new(0xCE, "DEC", 3, AddressingMode::Absolute, 6),
-        Instruction::new(0xDE, "DEC", 3, AddressingMode::AbsoluteX, 7),
-        Instruction::new(0xCA, "DEX", 1, AddressingMode::NonAddressing, 2),
-        Instruction::new(0x88, "DEY", 1, AddressingMode::NonAddressing, 2),
-
-        // --- Shifts ---
-        Instruction::new(0x0A, "ASL", 1, AddressingMode::NonAddressing, 2), // Accumulator
-        Instruction::new(0x06, "ASL", 2, AddressingMode::ZeroPage, 5),
-        Instruction::new(0x16, "ASL", 2, AddressingMode::ZeroPageX, 6),
-        Instruction::new(0x0E, "ASL", 3, AddressingMode::Absolute, 6),
-        Instruction::new(0x1E, "ASL", 3, AddressingMode::AbsoluteX, 7),
-        Instruction::new(0x4A, "LSR", 1, AddressingMode::NonAddressing, 2), // Accumulator
-        Instruction::new(0x46, "LSR", 2, AddressingMode::ZeroPage, 5),
-        Instruction::new(0x56, "LSR", 2, AddressingMode::ZeroPageX, 6),
-        Instruction::new(0x4E, "LSR", 3, AddressingMode::Absolute, 6),
-        Instruction::new(0x5E, "LSR", 3, AddressingMode::AbsoluteX, 7),
-        Instruction::new(0x2A, "ROL", 1, AddressingMode::NonAddressing, 2), // Accumulator
-        Instruction::new(0x26, "ROL", 2, AddressingMode::ZeroPage, 5),
-        Instruction::new(0x36, "ROL", 2, AddressingMode::ZeroPageX, 6),
-        Instruction::new(0x2E, "ROL", 3, AddressingMode::Absolute, 6),
-        Instruction::new(0x3E, "ROL", 3, AddressingMode::AbsoluteX, 7),
-        Instruction::new(0x6A, "ROR", 1, AddressingMode::NonAddressing, 2), // Accumulator
-        Instruction::new(0x66, "ROR", 2, AddressingMode::ZeroPage, 5),
-        Instruction::new(0x76, "ROR", 2, AddressingMode::ZeroPageX, 6),
-        Instruction::new(0x6E, "ROR", 3, AddressingMode::Absolute, 6),
-        Instruction::new(0x7E, "ROR", 3, AddressingMode::AbsoluteX, 7),
-
-        // --- Branches ---
-        Instruction::new(0x90, "BCC", 2, AddressingMode::NonAddressing, 2),
-        Instruction::new(0xB0, "BCS", 2, AddressingMode::NonAddressing, 2),
-        Instruction::new(0xF0, "BEQ", 2, AddressingMode::NonAddressing, 2),
-        Instruction::new(0xD0, "BNE", 2, AddressingMode::NonAddressing, 2),
-        Instruction::new(0x30, "BMI", 2, AddressingMode::NonAddressing, 2),
-        Instruction::new(0x10, "BPL", 2, AddressingMode::NonAddressing, 2),
-        Instruction::new(0x50, "BVC", 2, AddressingMode::NonAddressing, 2),
-        Instruction::new(0x70, "BVS", 2, AddressingMode::NonAddressing, 2),
-
-        // --- Status Flag Changes ---
-        Instruction::new(0x18, "CLC", 1, AddressingMode::NonAddressing, 2),
-        Instruction::new(0x38, "SEC", 1, AddressingMode::NonAddressing, 2),
-        Instruction::new(0x58, "CLI", 1, AddressingMode::NonAddressing, 2),
-        Instruction::new(0x78, "SEI", 1, AddressingMode::NonAddressing, 2),
-        Instruction::new(0xD8, "CLD", 1, AddressingMode::NonAddressing, 2),
-        Instruction::new(0xF8, "SED", 1, AddressingMode::NonAddressing, 2),
-        Instruction::new(0xB8, "CLV", 1, AddressingMode::NonAddressing, 2),
-    ];
-
-    pub static ref CPU_INSTRUCTIONS_MAP: HashMap<u8, &'static Instruction> = {
-        let mut map = HashMap::new();
-        for instruction in &*CPU_INSTRUCTIONS {
-            map.insert(instruction.code, instruction);
+pub static CPU_INSTRUCTIONS: &[Instruction] = &[
+    // --- System Functions ---
+    Instruction::new(0x00, "BRK", 1, AddressingMode::NonAddressing, 7),
+    Instruction::new(0xEA, "NOP", 1, AddressingMode::NonAddressing, 2),
+    Instruction::new(0x40, "RTI", 1, AddressingMode::NonAddressing, 6),
+
+    // --- Jumps & Subroutines ---
+    Instruction::new(0x4C, "JMP", 3, AddressingMode::Absolute, 3),
+    Instruction::new(0x6C, "JMP", 3, AddressingMode::Indirect, 5),
+    Instruction::new(0x20, "JSR", 3, AddressingMode::Absolute, 6),
+    Instruction::new(0x60, "RTS", 1, AddressingMode::NonAddressing, 6),
+
+    // --- Load/Store Operations ---
+    Instruction::new(0xA9, "LDA", 2, AddressingMode::Immediate, 2),
+    Instruction::new(0xA5, "LDA", 2, AddressingMode::ZeroPage, 3),
+    Instruction::new(0xB5, "LDA", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::new(0xAD, "LDA", 3, AddressingMode::Absolute, 4),
+    Instruction::new(0xBD, "LDA", 3, AddressingMode::AbsoluteX, 4),
+    Instruction::new(0xB9, "LDA", 3, AddressingMode::AbsoluteY, 4),
+    Instruction::new(0xA1, "LDA", 2, AddressingMode::IndirectX, 6),
+    Instruction::new(0xB1, "LDA", 2, AddressingMode::IndirectY, 5),
+    Instruction::new(0xA2, "LDX", 2, AddressingMode::Immediate, 2),
+    Instruction::new(0xA6, "LDX", 2, AddressingMode::ZeroPage, 3),
+    Instruction::new(0xB6, "LDX", 2, AddressingMode::ZeroPageY, 4),
+    Instruction::new(0xAE, "LDX", 3, AddressingMode::Absolute, 4),
+    Instruction::new(0xBE, "LDX", 3, AddressingMode::AbsoluteY, 4),
+    Instruction::new(0xA0, "LDY", 2, AddressingMode::Immediate, 2),
+    Instruction::new(0xA4, "LDY", 2, AddressingMode::ZeroPage, 3),
+    Instruction::new(0xB4, "LDY", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::new(0xAC, "LDY", 3, AddressingMode::Absolute, 4),
+    Instruction::new(0xBC, "LDY", 3, AddressingMode::AbsoluteX, 4),
+    Instruction::new(0x85, "STA", 2, AddressingMode::ZeroPage, 3),
+    Instruction::new(0x95, "STA", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::new(0x8D, "STA", 3, AddressingMode::Absolute, 4),
+    Instruction::new(0x9D, "STA", 3, AddressingMode::AbsoluteX, 5),
+    Instruction::new(0x99, "STA", 3, AddressingMode::AbsoluteY, 5),
+    Instruction::new(0x81, "STA", 2, AddressingMode::IndirectX, 6),
+    Instruction::new(0x91, "STA", 2, AddressingMode::IndirectY, 6),
+    Instruction::new(0x86, "STX", 2, AddressingMode::ZeroPage, 3),
+    Instruction::new(0x96, "STX", 2, AddressingMode::ZeroPageY, 4),
+    Instruction::new(0x8E, "STX", 3, AddressingMode::Absolute, 4),
+    Instruction::new(0x84, "STY", 2, AddressingMode::ZeroPage, 3),
+    Instruction::new(0x94, "STY", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::new(0x8C, "STY", 3, AddressingMode::Absolute, 4),
+
+    // --- Register Transfers ---
+    Instruction::new(0xAA, "TAX", 1, AddressingMode::NonAddressing, 2),
+    Instruction::new(0xA8, "TAY", 1, AddressingMode::NonAddressing, 2),
+    Instruction::new(0xBA, "TSX", 1, AddressingMode::NonAddressing, 2),
+    Instruction::new(0x8A, "TXA", 1, AddressingMode::NonAddressing, 2),
+    Instruction::new(0x9A, "TXS", 1, AddressingMode::NonAddressing, 2),
+    Instruction::new(0x98, "TYA", 1, AddressingMode::NonAddressing, 2),
+
+    // --- Stack Operations ---
+    Instruction::new(0x48, "PHA", 1, AddressingMode::NonAddressing, 3),
+    Instruction::new(0x08, "PHP", 1, AddressingMode::NonAddressing, 3),
+    Instruction::new(0x68, "PLA", 1, AddressingMode::NonAddressing, 4),
+    Instruction::new(0x28, "PLP", 1, AddressingMode::NonAddressing, 4),
+
+    // --- Logical ---
+    Instruction::new(0x29, "AND", 2, AddressingMode::Immediate, 2),
+    Instruction::new(0x25, "AND", 2, AddressingMode::ZeroPage, 3),
+    Instruction::new(0x35, "AND", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::new(0x2D, "AND", 3, AddressingMode::Absolute, 4),
+    Instruction::new(0x3D, "AND", 3, AddressingMode::AbsoluteX, 4),
+    Instruction::new(0x39, "AND", 3, AddressingMode::AbsoluteY, 4),
+    Instruction::new(0x21, "AND", 2, AddressingMode::IndirectX, 6),
+    Instruction::new(0x31, "AND", 2, AddressingMode::IndirectY, 5),
+    Instruction::new(0x49, "EOR", 2, AddressingMode::Immediate, 2),
+    Instruction::new(0x45, "EOR", 2, AddressingMode::ZeroPage, 3),
+    Instruction::new(0x55, "EOR", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::new(0x4D, "EOR", 3, AddressingMode::Absolute, 4),
+    Instruction::new(0x5D, "EOR", 3, AddressingMode::AbsoluteX, 4),
+    Instruction::new(0x59, "EOR", 3, AddressingMode::AbsoluteY, 4),
+    Instruction::new(0x41, "EOR", 2, AddressingMode::IndirectX, 6),
+    Instruction::new(0x51, "EOR", 2, AddressingMode::IndirectY, 5),
+    Instruction::new(0x09, "ORA", 2, AddressingMode::Immediate, 2),
+    Instruction::new(0x05, "ORA", 2, AddressingMode::ZeroPage, 3),
+    Instruction::new(0x15, "ORA", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::new(0x0D, "ORA", 3, AddressingMode::Absolute, 4),
+    Instruction::new(0x1D, "ORA", 3, AddressingMode::AbsoluteX, 4),
+    Instruction::new(0x19, "ORA", 3, AddressingMode::AbsoluteY, 4),
+    Instruction::new(0x01, "ORA", 2, AddressingMode::IndirectX, 6),
+    Instruction::new(0x11, "ORA", 2, AddressingMode::IndirectY, 5),
+    Instruction::new(0x24, "BIT", 2, AddressingMode::ZeroPage, 3),
+    Instruction::new(0x2C, "BIT", 3, AddressingMode::Absolute, 4),
+
+    // --- Arithmetic ---
+    Instruction::new(0x69, "ADC", 2, AddressingMode::Immediate, 2),
+    Instruction::new(0x65, "ADC", 2, AddressingMode::ZeroPage, 3),
+    Instruction::new(0x75, "ADC", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::new(0x6D, "ADC", 3, AddressingMode::Absolute, 4),
+    Instruction::new(0x7D, "ADC", 3, AddressingMode::AbsoluteX, 4),
+    Instruction::new(0x79, "ADC", 3, AddressingMode::AbsoluteY, 4),
+    Instruction::new(0x61, "ADC", 2, AddressingMode::IndirectX, 6),
+    Instruction::new(0x71, "ADC", 2, AddressingMode::IndirectY, 5),
+    Instruction::new(0xE9, "SBC", 2, AddressingMode::Immediate, 2),
+    Instruction::new(0xE5, "SBC", 2, AddressingMode::ZeroPage, 3),
+    Instruction::new(0xF5, "SBC", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::new(0xED, "SBC", 3, AddressingMode::Absolute, 4),
+    Instruction::new(0xFD, "SBC", 3, AddressingMode::AbsoluteX, 4),
+    Instruction::new(0xF9, "SBC", 3, AddressingMode::AbsoluteY, 4),
+    Instruction::new(0xE1, "SBC", 2, AddressingMode::IndirectX, 6),
+    Instruction::new(0xF1, "SBC", 2, AddressingMode::IndirectY, 5),
+
+    // --- Comparisons ---
+    Instruction::new(0xC9, "CMP", 2, AddressingMode::Immediate, 2),
+    Instruction::new(0xC5, "CMP", 2, AddressingMode::ZeroPage, 3),
+    Instruction::new(0xD5, "CMP", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::new(0xCD, "CMP", 3, AddressingMode::Absolute, 4),
+    Instruction::new(0xDD, "CMP", 3, AddressingMode::AbsoluteX, 4),
+    Instruction::new(0xD9, "CMP", 3, AddressingMode::AbsoluteY, 4),
+    Instruction::new(0xC1, "CMP", 2, AddressingMode::IndirectX, 6),
+    Instruction::new(0xD1, "CMP", 2, AddressingMode::IndirectY, 5),
+    Instruction::new(0xE0, "CPX", 2, AddressingMode::Immediate, 2),
+    Instruction::new(0xE4, "CPX", 2, AddressingMode::ZeroPage, 3),
+    Instruction::new(0xEC, "CPX", 3, AddressingMode::Absolute, 4),
+    Instruction::new(0xC0, "CPY", 2, AddressingMode::Immediate, 2),
+    Instruction::new(0xC4, "CPY", 2, AddressingMode::ZeroPage, 3),
+    Instruction::new(0xCC, "CPY", 3, AddressingMode::Absolute, 4),
+
+    // --- Increments & Decrements ---
+    Instruction::new(0xE6, "INC", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0xF6, "INC", 2, AddressingMode::ZeroPageX, 6),
+    Instruction::new(0xEE, "INC", 3, AddressingMode::Absolute, 6),
+    Instruction::new(0xFE, "INC", 3, AddressingMode::AbsoluteX, 7),
+    Instruction::new(0xE8, "INX", 1, AddressingMode::NonAddressing, 2),
+    Instruction::new(0xC8, "INY", 1, AddressingMode::NonAddressing, 2),
+    Instruction::new(0xC6, "DEC", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0xD6, "DEC", 2, AddressingMode::ZeroPageX, 6),
+    Instruction::new(0xCE, "DEC", 3, AddressingMode::Absolute, 6),
+    Instruction::new(0xDE, "DEC", 3, AddressingMode::AbsoluteX, 7),
+    Instruction::new(0xCA, "DEX", 1, AddressingMode::NonAddressing, 2),
+    Instruction::new(0x88, "DEY", 1, AddressingMode::NonAddressing, 2),
+
+    // --- Shifts ---
+    Instruction::new(0x0A, "ASL", 1, AddressingMode::Accumulator, 2),
+    Instruction::new(0x06, "ASL", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0x16, "ASL", 2, AddressingMode::ZeroPageX, 6),
+    Instruction::new(0x0E, "ASL", 3, AddressingMode::Absolute, 6),
+    Instruction::new(0x1E, "ASL", 3, AddressingMode::AbsoluteX, 7),
+    Instruction::new(0x4A, "LSR", 1, AddressingMode::Accumulator, 2),
+    Instruction::new(0x46, "LSR", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0x56, "LSR", 2, AddressingMode::ZeroPageX, 6),
+    Instruction::new(0x4E, "LSR", 3, AddressingMode::Absolute, 6),
+    Instruction::new(0x5E, "LSR", 3, AddressingMode::AbsoluteX, 7),
+    Instruction::new(0x2A, "ROL", 1, AddressingMode::Accumulator, 2),
+    Instruction::new(0x26, "ROL", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0x36, "ROL", 2, AddressingMode::ZeroPageX, 6),
+    Instruction::new(0x2E, "ROL", 3, AddressingMode::Absolute, 6),
+    Instruction::new(0x3E, "ROL", 3, AddressingMode::AbsoluteX, 7),
+    Instruction::new(0x6A, "ROR", 1, AddressingMode::Accumulator, 2),
+    Instruction::new(0x66, "ROR", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0x76, "ROR", 2, AddressingMode::ZeroPageX, 6),
+    Instruction::new(0x6E, "ROR", 3, AddressingMode::Absolute, 6),
+    Instruction::new(0x7E, "ROR", 3, AddressingMode::AbsoluteX, 7),
+
+    // --- Branches ---
+    Instruction::new(0x90, "BCC", 2, AddressingMode::Relative, 2),
+    Instruction::new(0xB0, "BCS", 2, AddressingMode::Relative, 2),
+    Instruction::new(0xF0, "BEQ", 2, AddressingMode::Relative, 2),
+    Instruction::new(0xD0, "BNE", 2, AddressingMode::Relative, 2),
+    Instruction::new(0x30, "BMI", 2, AddressingMode::Relative, 2),
+    Instruction::new(0x10, "BPL", 2, AddressingMode::Relative, 2),
+    Instruction::new(0x50, "BVC", 2, AddressingMode::Relative, 2),
+    Instruction::new(0x70, "BVS", 2, AddressingMode::Relative, 2),
+
+    // --- Status Flag Changes ---
+    Instruction::new(0x18, "CLC", 1, AddressingMode::NonAddressing, 2),
+    Instruction::new(0x38, "SEC", 1, AddressingMode::NonAddressing, 2),
+    Instruction::new(0x58, "CLI", 1, AddressingMode::NonAddressing, 2),
+    Instruction::new(0x78, "SEI", 1, AddressingMode::NonAddressing, 2),
+    Instruction::new(0xD8, "CLD", 1, AddressingMode::NonAddressing, 2),
+    Instruction::new(0xF8, "SED", 1, AddressingMode::NonAddressing, 2),
+    Instruction::new(0xB8, "CLV", 1, AddressingMode::NonAddressing, 2),
+
+    // --- Unofficial: NOPs ---
+    Instruction::unofficial(0x1A, "NOP", 1, AddressingMode::NonAddressing, 2),
+    Instruction::unofficial(0x3A, "NOP", 1, AddressingMode::NonAddressing, 2),
+    Instruction::unofficial(0x5A, "NOP", 1, AddressingMode::NonAddressing, 2),
+    Instruction::unofficial(0x7A, "NOP", 1, AddressingMode::NonAddressing, 2),
+    Instruction::unofficial(0xDA, "NOP", 1, AddressingMode::NonAddressing, 2),
+    Instruction::unofficial(0xFA, "NOP", 1, AddressingMode::NonAddressing, 2),
+    Instruction::unofficial(0x80, "NOP", 2, AddressingMode::Immediate, 2), // DOP
+    Instruction::unofficial(0x82, "NOP", 2, AddressingMode::Immediate, 2),
+    Instruction::unofficial(0x89, "NOP", 2, AddressingMode::Immediate, 2),
+    Instruction::unofficial(0xC2, "NOP", 2, AddressingMode::Immediate, 2),
+    Instruction::unofficial(0xE2, "NOP", 2, AddressingMode::Immediate, 2),
+    Instruction::unofficial(0x04, "NOP", 2, AddressingMode::ZeroPage, 3),
+    Instruction::unofficial(0x44, "NOP", 2, AddressingMode::ZeroPage, 3),
+    Instruction::unofficial(0x64, "NOP", 2, AddressingMode::ZeroPage, 3),
+    Instruction::unofficial(0x14, "NOP", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::unofficial(0x34, "NOP", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::unofficial(0x54, "NOP", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::unofficial(0x74, "NOP", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::unofficial(0xD4, "NOP", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::unofficial(0xF4, "NOP", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::unofficial(0x0C, "NOP", 3, AddressingMode::Absolute, 4), // TOP
+    Instruction::unofficial(0x1C, "NOP", 3, AddressingMode::AbsoluteX, 4),
+    Instruction::unofficial(0x3C, "NOP", 3, AddressingMode::AbsoluteX, 4),
+    Instruction::unofficial(0x5C, "NOP", 3, AddressingMode::AbsoluteX, 4),
+    Instruction::unofficial(0x7C, "NOP", 3, AddressingMode::AbsoluteX, 4),
+    Instruction::unofficial(0xDC, "NOP", 3, AddressingMode::AbsoluteX, 4),
+    Instruction::unofficial(0xFC, "NOP", 3, AddressingMode::AbsoluteX, 4),
+
+    // --- Unofficial: Combined Load/Store ---
+    Instruction::unofficial(0xA7, "LAX", 2, AddressingMode::ZeroPage, 3),
+    Instruction::unofficial(0xB7, "LAX", 2, AddressingMode::ZeroPageY, 4),
+    Instruction::unofficial(0xAF, "LAX", 3, AddressingMode::Absolute, 4),
+    Instruction::unofficial(0xBF, "LAX", 3, AddressingMode::AbsoluteY, 4),
+    Instruction::unofficial(0xA3, "LAX", 2, AddressingMode::IndirectX, 6),
+    Instruction::unofficial(0xB3, "LAX", 2, AddressingMode::IndirectY, 5),
+    Instruction::unofficial(0x87, "SAX", 2, AddressingMode::ZeroPage, 3),
+    Instruction::unofficial(0x97, "SAX", 2, AddressingMode::ZeroPageY, 4),
+    Instruction::unofficial(0x8F, "SAX", 3, AddressingMode::Absolute, 4),
+    Instruction::unofficial(0x83, "SAX", 2, AddressingMode::IndirectX, 6),
+
+    // --- Unofficial: Read-Modify-Write Combos ---
+    Instruction::unofficial(0xC7, "DCP", 2, AddressingMode::ZeroPage, 5),
+    Instruction::unofficial(0xD7, "DCP", 2, AddressingMode::ZeroPageX, 6),
+    Instruction::unofficial(0xCF, "DCP", 3, AddressingMode::Absolute, 6),
+    Instruction::unofficial(0xDF, "DCP", 3, AddressingMode::AbsoluteX, 7),
+    Instruction::unofficial(0xDB, "DCP", 3, AddressingMode::AbsoluteY, 7),
+    Instruction::unofficial(0xC3, "DCP", 2, AddressingMode::IndirectX, 8),
+    Instruction::unofficial(0xD3, "DCP", 2, AddressingMode::IndirectY, 8),
+    Instruction::unofficial(0xE7, "ISB", 2, AddressingMode::ZeroPage, 5),
+    Instruction::unofficial(0xF7, "ISB", 2, AddressingMode::ZeroPageX, 6),
+    Instruction::unofficial(0xEF, "ISB", 3, AddressingMode::Absolute, 6),
+    Instruction::unofficial(0xFF, "ISB", 3, AddressingMode::AbsoluteX, 7),
+    Instruction::unofficial(0xFB, "ISB", 3, AddressingMode::AbsoluteY, 7),
+    Instruction::unofficial(0xE3, "ISB", 2, AddressingMode::IndirectX, 8),
+    Instruction::unofficial(0xF3, "ISB", 2, AddressingMode::IndirectY, 8),
+    Instruction::unofficial(0x07, "SLO", 2, AddressingMode::ZeroPage, 5),
+    Instruction::unofficial(0x17, "SLO", 2, AddressingMode::ZeroPageX, 6),
+    Instruction::unofficial(0x0F, "SLO", 3, AddressingMode::Absolute, 6),
+    Instruction::unofficial(0x1F, "SLO", 3, AddressingMode::AbsoluteX, 7),
+    Instruction::unofficial(0x1B, "SLO", 3, AddressingMode::AbsoluteY, 7),
+    Instruction::unofficial(0x03, "SLO", 2, AddressingMode::IndirectX, 8),
+    Instruction::unofficial(0x13, "SLO", 2, AddressingMode::IndirectY, 8),
+    Instruction::unofficial(0x27, "RLA", 2, AddressingMode::ZeroPage, 5),
+    Instruction::unofficial(0x37, "RLA", 2, AddressingMode::ZeroPageX, 6),
+    Instruction::unofficial(0x2F, "RLA", 3, AddressingMode::Absolute, 6),
+    Instruction::unofficial(0x3F, "RLA", 3, AddressingMode::AbsoluteX, 7),
+    Instruction::unofficial(0x3B, "RLA", 3, AddressingMode::AbsoluteY, 7),
+    Instruction::unofficial(0x23, "RLA", 2, AddressingMode::IndirectX, 8),
+    Instruction::unofficial(0x33, "RLA", 2, AddressingMode::IndirectY, 8),
+    Instruction::unofficial(0x47, "SRE", 2, AddressingMode::ZeroPage, 5),
+    Instruction::unofficial(0x57, "SRE", 2, AddressingMode::ZeroPageX, 6),
+    Instruction::unofficial(0x4F, "SRE", 3, AddressingMode::Absolute, 6),
+    Instruction::unofficial(0x5F, "SRE", 3, AddressingMode::AbsoluteX, 7),
+    Instruction::unofficial(0x5B, "SRE", 3, AddressingMode::AbsoluteY, 7),
+    Instruction::unofficial(0x43, "SRE", 2, AddressingMode::IndirectX, 8),
+    Instruction::unofficial(0x53, "SRE", 2, AddressingMode::IndirectY, 8),
+    Instruction::unofficial(0x67, "RRA", 2, AddressingMode::ZeroPage, 5),
+    Instruction::unofficial(0x77, "RRA", 2, AddressingMode::ZeroPageX, 6),
+    Instruction::unofficial(0x6F, "RRA", 3, AddressingMode::Absolute, 6),
+    Instruction::unofficial(0x7F, "RRA", 3, AddressingMode::AbsoluteX, 7),
+    Instruction::unofficial(0x7B, "RRA", 3, AddressingMode::AbsoluteY, 7),
+    Instruction::unofficial(0x63, "RRA", 2, AddressingMode::IndirectX, 8),
+    Instruction::unofficial(0x73, "RRA", 2, AddressingMode::IndirectY, 8),
+
+    // --- Unofficial: Immediate Combos ---
+    Instruction::unofficial(0xEB, "SBC", 2, AddressingMode::Immediate, 2),
+    Instruction::unofficial(0x0B, "ANC", 2, AddressingMode::Immediate, 2),
+    Instruction::unofficial(0x2B, "ANC", 2, AddressingMode::Immediate, 2),
+    Instruction::unofficial(0x4B, "ALR", 2, AddressingMode::Immediate, 2),
+    Instruction::unofficial(0x6B, "ARR", 2, AddressingMode::Immediate, 2),
+    Instruction::unofficial(0xCB, "AXS", 2, AddressingMode::Immediate, 2),
+
+    // --- Unofficial: Unstable ---
+    Instruction::unofficial(0x8B, "XAA", 2, AddressingMode::Immediate, 2),
+    Instruction::unofficial(0xAB, "LAX", 2, AddressingMode::Immediate, 2),
+    Instruction::unofficial(0x9E, "SHX", 3, AddressingMode::AbsoluteY, 5),
+    Instruction::unofficial(0x9C, "SHY", 3, AddressingMode::AbsoluteX, 5),
+    Instruction::unofficial(0x9F, "AHX", 3, AddressingMode::AbsoluteY, 5),
+    Instruction::unofficial(0x93, "AHX", 2, AddressingMode::IndirectY, 6),
+    Instruction::unofficial(0x9B, "TAS", 3, AddressingMode::AbsoluteY, 5),
+    Instruction::unofficial(0xBB, "LAS", 3, AddressingMode::AbsoluteY, 4),
+
+    // --- Unofficial: Halts the CPU ---
+    Instruction::unofficial(0x02, "JAM", 1, AddressingMode::NonAddressing, 2),
+    Instruction::unofficial(0x12, "JAM", 1, AddressingMode::NonAddressing, 2),
+    Instruction::unofficial(0x22, "JAM", 1, AddressingMode::NonAddressing, 2),
+    Instruction::unofficial(0x32, "JAM", 1, AddressingMode::NonAddressing, 2),
+    Instruction::unofficial(0x42, "JAM", 1, AddressingMode::NonAddressing, 2),
+    Instruction::unofficial(0x52, "JAM", 1, AddressingMode::NonAddressing, 2),
+    Instruction::unofficial(0x62, "JAM", 1, AddressingMode::NonAddressing, 2),
+    Instruction::unofficial(0x72, "JAM", 1, AddressingMode::NonAddressing, 2),
+    Instruction::unofficial(0x92, "JAM", 1, AddressingMode::NonAddressing, 2),
+    Instruction::unofficial(0xB2, "JAM", 1, AddressingMode::NonAddressing, 2),
+    Instruction::unofficial(0xD2, "JAM", 1, AddressingMode::NonAddressing, 2),
+    Instruction::unofficial(0xF2, "JAM", 1, AddressingMode::NonAddressing, 2),
+];
+
+// what the 65C02 puts in the NMOS opcode slots it changed or filled; every other official opcode is shared
+pub static CMOS_INSTRUCTIONS: &[Instruction] = &[
+    // --- Changed from NMOS ---
+    Instruction::new(0x6C, "JMP", 3, AddressingMode::Indirect, 6), // no page wrap bug, one cycle longer
+    Instruction::new(0x1E, "ASL", 3, AddressingMode::AbsoluteX, 6), // +1 if page crossed
+    Instruction::new(0x3E, "ROL", 3, AddressingMode::AbsoluteX, 6),
+    Instruction::new(0x5E, "LSR", 3, AddressingMode::AbsoluteX, 6),
+    Instruction::new(0x7E, "ROR", 3, AddressingMode::AbsoluteX, 6),
+
+    // --- New Branch, Jump and Stack ---
+    Instruction::new(0x80, "BRA", 2, AddressingMode::Relative, 3),
+    Instruction::new(0x7C, "JMP", 3, AddressingMode::AbsoluteIndexedIndirect, 6),
+    Instruction::new(0xDA, "PHX", 1, AddressingMode::NonAddressing, 3),
+    Instruction::new(0x5A, "PHY", 1, AddressingMode::NonAddressing, 3),
+    Instruction::new(0xFA, "PLX", 1, AddressingMode::NonAddressing, 4),
+    Instruction::new(0x7A, "PLY", 1, AddressingMode::NonAddressing, 4),
+
+    // --- New Loads, Stores and Arithmetic ---
+    Instruction::new(0x64, "STZ", 2, AddressingMode::ZeroPage, 3),
+    Instruction::new(0x74, "STZ", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::new(0x9C, "STZ", 3, AddressingMode::Absolute, 4),
+    Instruction::new(0x9E, "STZ", 3, AddressingMode::AbsoluteX, 5),
+    Instruction::new(0x1A, "INC", 1, AddressingMode::Accumulator, 2),
+    Instruction::new(0x3A, "DEC", 1, AddressingMode::Accumulator, 2),
+    Instruction::new(0x89, "BIT", 2, AddressingMode::Immediate, 2),
+    Instruction::new(0x34, "BIT", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::new(0x3C, "BIT", 3, AddressingMode::AbsoluteX, 4), // +1 if page crossed
+    Instruction::new(0x04, "TSB", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0x0C, "TSB", 3, AddressingMode::Absolute, 6),
+    Instruction::new(0x14, "TRB", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0x1C, "TRB", 3, AddressingMode::Absolute, 6),
+
+    // --- Zero Page Indirect ---
+    Instruction::new(0x12, "ORA", 2, AddressingMode::ZeroPageIndirect, 5),
+    Instruction::new(0x32, "AND", 2, AddressingMode::ZeroPageIndirect, 5),
+    Instruction::new(0x52, "EOR", 2, AddressingMode::ZeroPageIndirect, 5),
+    Instruction::new(0x72, "ADC", 2, AddressingMode::ZeroPageIndirect, 5),
+    Instruction::new(0x92, "STA", 2, AddressingMode::ZeroPageIndirect, 5),
+    Instruction::new(0xB2, "LDA", 2, AddressingMode::ZeroPageIndirect, 5),
+    Instruction::new(0xD2, "CMP", 2, AddressingMode::ZeroPageIndirect, 5),
+    Instruction::new(0xF2, "SBC", 2, AddressingMode::ZeroPageIndirect, 5),
+
+    // --- Bit Manipulation (Rockwell/WDC) ---
+    Instruction::new(0x07, "RMB0", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0x17, "RMB1", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0x27, "RMB2", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0x37, "RMB3", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0x47, "RMB4", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0x57, "RMB5", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0x67, "RMB6", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0x77, "RMB7", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0x87, "SMB0", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0x97, "SMB1", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0xA7, "SMB2", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0xB7, "SMB3", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0xC7, "SMB4", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0xD7, "SMB5", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0xE7, "SMB6", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0xF7, "SMB7", 2, AddressingMode::ZeroPage, 5),
+    Instruction::new(0x0F, "BBR0", 3, AddressingMode::ZeroPageRelative, 5),
+    Instruction::new(0x1F, "BBR1", 3, AddressingMode::ZeroPageRelative, 5),
+    Instruction::new(0x2F, "BBR2", 3, AddressingMode::ZeroPageRelative, 5),
+    Instruction::new(0x3F, "BBR3", 3, AddressingMode::ZeroPageRelative, 5),
+    Instruction::new(0x4F, "BBR4", 3, AddressingMode::ZeroPageRelative, 5),
+    Instruction::new(0x5F, "BBR5", 3, AddressingMode::ZeroPageRelative, 5),
+    Instruction::new(0x6F, "BBR6", 3, AddressingMode::ZeroPageRelative, 5),
+    Instruction::new(0x7F, "BBR7", 3, AddressingMode::ZeroPageRelative, 5),
+    Instruction::new(0x8F, "BBS0", 3, AddressingMode::ZeroPageRelative, 5),
+    Instruction::new(0x9F, "BBS1", 3, AddressingMode::ZeroPageRelative, 5),
+    Instruction::new(0xAF, "BBS2", 3, AddressingMode::ZeroPageRelative, 5),
+    Instruction::new(0xBF, "BBS3", 3, AddressingMode::ZeroPageRelative, 5),
+    Instruction::new(0xCF, "BBS4", 3, AddressingMode::ZeroPageRelative, 5),
+    Instruction::new(0xDF, "BBS5", 3, AddressingMode::ZeroPageRelative, 5),
+    Instruction::new(0xEF, "BBS6", 3, AddressingMode::ZeroPageRelative, 5),
+    Instruction::new(0xFF, "BBS7", 3, AddressingMode::ZeroPageRelative, 5),
+
+    // --- Wait and Stop (WDC) ---
+    Instruction::new(0xCB, "WAI", 1, AddressingMode::NonAddressing, 3),
+    Instruction::new(0xDB, "STP", 1, AddressingMode::NonAddressing, 3),
+
+    // --- Reserved: NOPs of various sizes, the 1 byte ones take a single cycle ---
+    Instruction::unofficial(0x03, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x13, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x23, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x33, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x43, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x53, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x63, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x73, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x83, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x93, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0xA3, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0xB3, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0xC3, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0xD3, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0xE3, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0xF3, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x0B, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x1B, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x2B, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x3B, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x4B, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x5B, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x6B, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x7B, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x8B, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x9B, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0xAB, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0xBB, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0xEB, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0xFB, "NOP", 1, AddressingMode::NonAddressing, 1),
+    Instruction::unofficial(0x02, "NOP", 2, AddressingMode::Immediate, 2),
+    Instruction::unofficial(0x22, "NOP", 2, AddressingMode::Immediate, 2),
+    Instruction::unofficial(0x42, "NOP", 2, AddressingMode::Immediate, 2),
+    Instruction::unofficial(0x62, "NOP", 2, AddressingMode::Immediate, 2),
+    Instruction::unofficial(0x82, "NOP", 2, AddressingMode::Immediate, 2),
+    Instruction::unofficial(0xC2, "NOP", 2, AddressingMode::Immediate, 2),
+    Instruction::unofficial(0xE2, "NOP", 2, AddressingMode::Immediate, 2),
+    Instruction::unofficial(0x44, "NOP", 2, AddressingMode::ZeroPage, 3),
+    Instruction::unofficial(0x54, "NOP", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::unofficial(0xD4, "NOP", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::unofficial(0xF4, "NOP", 2, AddressingMode::ZeroPageX, 4),
+    Instruction::unofficial(0x5C, "NOP", 3, AddressingMode::Absolute, 8),
+    Instruction::unofficial(0xDC, "NOP", 3, AddressingMode::Absolute, 4),
+    Instruction::unofficial(0xFC, "NOP", 3, AddressingMode::Absolute, 4),
+];
+
+// opcode byte -> instruction, built from CPU_INSTRUCTIONS at compile time
+pub static DECODE_TABLE: [Option<&Instruction>; 256] = build_decode_table(CPU_INSTRUCTIONS);
+
+// the 65C02 decode table: the official NMOS instructions overlaid with CMOS_INSTRUCTIONS
+pub static CMOS_DECODE_TABLE: [Option<&Instruction>; 256] = build_cmos_decode_table();
+
+const fn build_decode_table(instructions: &'static [Instruction]) -> [Option<&'static Instruction>; 256] {
+    let mut table = [None; 256];
+    let mut i = 0;
+    while i < instructions.len() {
+        table[instructions[i].code as usize] = Some(&instructions[i]);
+        i += 1;
+    }
+    table
+}
+
+const fn build_cmos_decode_table() -> [Option<&'static Instruction>; 256] {
+    let mut table = [None; 256];
+    let mut i = 0;
+    while i < CPU_INSTRUCTIONS.len() {
+        if !CPU_INSTRUCTIONS[i].unofficial {
+            table[CPU_INSTRUCTIONS[i].code as usize] = Some(&CPU_INSTRUCTIONS[i]);
         }
-        map
-    };
-}
\ No newline at end of file
+        i += 1;
+    }
+    let mut i = 0;
+    while i < CMOS_INSTRUCTIONS.len() {
+        table[CMOS_INSTRUCTIONS[i].code as usize] = Some(&CMOS_INSTRUCTIONS[i]);
+        i += 1;
+    }
+    table
+}
+
+pub fn decode_table(variant: CpuVariant) -> &'static [Option<&'static Instruction>; 256] {
+    match variant {
+        CpuVariant::Nes2A03 | CpuVariant::Nmos6502 => &DECODE_TABLE,
+        CpuVariant::Cmos65C02 => &CMOS_DECODE_TABLE,
+    }
+}
@@ -0,0 +1,34 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::mapper::Mapper;
+
+// battery-backed RAM and EEPROM contents are kept next to the ROM, as <rom name>.sav
+pub fn path_for(rom_path: &Path) -> PathBuf {
+    rom_path.with_extension("sav")
+}
+
+// restores a previous save into the board; a missing file just means there isn't one yet
+pub fn load(mapper: &mut dyn Mapper, path: &Path) -> io::Result<()> {
+    let Some(memory) = mapper.save_ram_mut() else {
+        return Ok(());
+    };
+    match fs::read(path) {
+        Ok(data) => {
+            let len = data.len().min(memory.len());
+            memory[..len].copy_from_slice(&data[..len]);
+            Ok(())
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+// writes the board's save memory out, if it has any
+pub fn store(mapper: &dyn Mapper, path: &Path) -> io::Result<()> {
+    match mapper.save_ram() {
+        Some(memory) => fs::write(path, memory),
+        None => Ok(()),
+    }
+}
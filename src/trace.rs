@@ -0,0 +1,145 @@
+use crate::cpu::{AddressingMode, CpuVariant, Memory, CPU};
+use crate::opcodes;
+
+// NTSC timing, used to derive the PPU column until there is a real PPU to ask
+const PPU_DOTS_PER_CPU_CYCLE: u64 = 3;
+const PPU_DOTS_PER_SCANLINE: u64 = 341;
+const PPU_SCANLINES_PER_FRAME: u64 = 262;
+
+// formats the instruction at pc in the nestest.log style, only peeking at memory so I/O is untouched
+// C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7
+pub fn trace<M: Memory>(cpu: &CPU<M>) -> String {
+    let pc = cpu.pc;
+    let opcode = cpu.peek(pc);
+
+    let (hex_dump, asm) = match opcodes::decode_table(cpu.variant())[opcode as usize] {
+        Some(instruction) => {
+            let hex_dump: Vec<u8> = (0..instruction.len as u16)
+                .map(|i| cpu.peek(pc.wrapping_add(i)))
+                .collect();
+            let mnemonic = if instruction.unofficial {
+                format!("*{}", instruction.mnemonic)
+            } else {
+                instruction.mnemonic.to_string()
+            };
+            let operand = format_operand(cpu, instruction, pc);
+            (hex_dump, format!("{:>4} {}", mnemonic, operand))
+        }
+        None => (vec![opcode], " ???".to_string()),
+    };
+
+    let hex_str = hex_dump
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<String>>()
+        .join(" ");
+    let asm_str = format!("{:04X}  {:8} {}", pc, hex_str, asm);
+
+    let dots = cpu.cycles * PPU_DOTS_PER_CPU_CYCLE;
+    let scanline = (dots / PPU_DOTS_PER_SCANLINE) % PPU_SCANLINES_PER_FRAME;
+    let dot = dots % PPU_DOTS_PER_SCANLINE;
+
+    format!(
+        "{:47} A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} PPU:{:>3},{:>3} CYC:{}",
+        asm_str.trim_end(),
+        cpu.acc,
+        cpu.index_x,
+        cpu.index_y,
+        cpu.status,
+        cpu.sp,
+        scanline,
+        dot,
+        cpu.cycles
+    )
+}
+
+fn format_operand<M: Memory>(cpu: &CPU<M>, instruction: &opcodes::Instruction, pc: u16) -> String {
+    let operand_pc = pc.wrapping_add(1);
+    let byte = cpu.peek(operand_pc);
+    let word = cpu.peek_u16(operand_pc);
+
+    match instruction.mode {
+        AddressingMode::NonAddressing => String::new(),
+        AddressingMode::Accumulator => "A".to_string(),
+        AddressingMode::Immediate => format!("#${:02X}", byte),
+        AddressingMode::ZeroPage => {
+            format!("${:02X} = {:02X}", byte, cpu.peek(byte as u16))
+        }
+        AddressingMode::ZeroPageX => {
+            let addr = byte.wrapping_add(cpu.index_x) as u16;
+            format!("${:02X},X @ {:02X} = {:02X}", byte, addr, cpu.peek(addr))
+        }
+        AddressingMode::ZeroPageY => {
+            let addr = byte.wrapping_add(cpu.index_y) as u16;
+            format!("${:02X},Y @ {:02X} = {:02X}", byte, addr, cpu.peek(addr))
+        }
+        AddressingMode::Absolute => match instruction.mnemonic {
+            // the operand of a jump is a destination, not something read
+            "JMP" | "JSR" => format!("${:04X}", word),
+            _ => format!("${:04X} = {:02X}", word, cpu.peek(word)),
+        },
+        AddressingMode::AbsoluteX => {
+            let addr = word.wrapping_add(cpu.index_x as u16);
+            format!("${:04X},X @ {:04X} = {:02X}", word, addr, cpu.peek(addr))
+        }
+        AddressingMode::AbsoluteY => {
+            let addr = word.wrapping_add(cpu.index_y as u16);
+            format!("${:04X},Y @ {:04X} = {:02X}", word, addr, cpu.peek(addr))
+        }
+        AddressingMode::IndirectX => {
+            let ptr = byte.wrapping_add(cpu.index_x);
+            let addr = peek_zero_page_u16(cpu, ptr);
+            format!(
+                "(${:02X},X) @ {:02X} = {:04X} = {:02X}",
+                byte,
+                ptr,
+                addr,
+                cpu.peek(addr)
+            )
+        }
+        AddressingMode::IndirectY => {
+            let base = peek_zero_page_u16(cpu, byte);
+            let addr = base.wrapping_add(cpu.index_y as u16);
+            format!(
+                "(${:02X}),Y = {:04X} @ {:04X} = {:02X}",
+                byte,
+                base,
+                addr,
+                cpu.peek(addr)
+            )
+        }
+        AddressingMode::Indirect => {
+            // reproduces the NMOS JMP ($xxFF) page wrap bug, which the 65C02 fixed
+            let hi_addr = match cpu.variant() {
+                CpuVariant::Cmos65C02 => word.wrapping_add(1),
+                _ => (word & 0xFF00) | (word.wrapping_add(1) & 0x00FF),
+            };
+            let lo = cpu.peek(word);
+            let hi = cpu.peek(hi_addr);
+            format!("(${:04X}) = {:04X}", word, (hi as u16) << 8 | lo as u16)
+        }
+        AddressingMode::Relative => {
+            let target = operand_pc.wrapping_add(1).wrapping_add(byte as i8 as u16);
+            format!("${:04X}", target)
+        }
+        AddressingMode::ZeroPageIndirect => {
+            let addr = peek_zero_page_u16(cpu, byte);
+            format!("(${:02X}) = {:04X} = {:02X}", byte, addr, cpu.peek(addr))
+        }
+        AddressingMode::AbsoluteIndexedIndirect => {
+            let ptr = word.wrapping_add(cpu.index_x as u16);
+            format!("(${:04X},X) @ {:04X} = {:04X}", word, ptr, cpu.peek_u16(ptr))
+        }
+        AddressingMode::ZeroPageRelative => {
+            let offset = cpu.peek(operand_pc.wrapping_add(1));
+            let target = operand_pc.wrapping_add(2).wrapping_add(offset as i8 as u16);
+            format!("${:02X} = {:02X}, ${:04X}", byte, cpu.peek(byte as u16), target)
+        }
+    }
+}
+
+fn peek_zero_page_u16<M: Memory>(cpu: &CPU<M>, ptr: u8) -> u16 {
+    let lo = cpu.peek(ptr as u16);
+    let hi = cpu.peek(ptr.wrapping_add(1) as u16);
+    (hi as u16) << 8 | lo as u16
+}
@@ -0,0 +1,121 @@
+use redNES::asm::{self, AsmError, Segment};
+
+fn segments(source: &str) -> Vec<Segment> {
+    asm::assemble(source).expect("source should assemble").segments
+}
+
+fn error(source: &str) -> AsmError {
+    asm::assemble(source).expect_err("source should not assemble")
+}
+
+#[test]
+fn forward_labels_resolve_in_the_second_pass() {
+    let program = asm::assemble(
+        "
+        .org $0600
+        JMP end
+        BNE end
+end:    RTS
+",
+    )
+    .unwrap();
+    assert_eq!(program.label("end"), Some(0x0605));
+    assert_eq!(
+        program.segments,
+        vec![Segment { origin: 0x0600, bytes: vec![0x4C, 0x05, 0x06, 0xD0, 0x00, 0x60] }]
+    );
+}
+
+#[test]
+fn zero_page_is_used_when_the_value_is_known_to_fit() {
+    let bytes = &segments(
+        "
+        .org $0600
+ZP = $20
+        LDA $10
+        LDA $1234
+        LDA ZP
+        LDX $10,Y
+        LDA later,X     ; not known yet, so absolute even though it ends up in zero page
+        .org $0080
+later:  .byte 0
+",
+    )[0]
+    .bytes;
+    assert_eq!(bytes, &[0xA5, 0x10, 0xAD, 0x34, 0x12, 0xA5, 0x20, 0xB6, 0x10, 0xBD, 0x80, 0x00]);
+}
+
+#[test]
+fn low_and_high_byte_operators() {
+    let bytes = &segments(
+        "
+        .org $0600
+        LDA #<data
+        LDX #>data
+        LDY #<data+2
+        .org $1234
+data:   .byte 0
+",
+    )[0]
+    .bytes;
+    assert_eq!(bytes, &[0xA9, 0x34, 0xA2, 0x12, 0xA0, 0x36]);
+}
+
+#[test]
+fn org_starts_a_new_segment() {
+    let program = asm::assemble_at(
+        "
+        NOP
+        .org $8000
+        RTS
+        .org $8001      ; still a segment of its own
+        RTI
+        .org $FFFC
+        .word $8000
+",
+        0x0600,
+    )
+    .unwrap();
+    assert_eq!(
+        program.segments,
+        vec![
+            Segment { origin: 0x0600, bytes: vec![0xEA] },
+            Segment { origin: 0x8000, bytes: vec![0x60] },
+            Segment { origin: 0x8001, bytes: vec![0x40] },
+            Segment { origin: 0xFFFC, bytes: vec![0x00, 0x80] },
+        ]
+    );
+}
+
+#[test]
+fn byte_and_word_directives() {
+    let bytes = &segments(
+        "
+        .org $0300
+table:  .byte $01, 2, %11, <table, >table
+        .word $1234, table, table+1
+",
+    )[0]
+    .bytes;
+    assert_eq!(bytes, &[0x01, 0x02, 0x03, 0x00, 0x03, 0x34, 0x12, 0x00, 0x03, 0x01, 0x03]);
+}
+
+#[test]
+fn errors_name_the_line() {
+    let cases = [
+        (
+            "        .org $0600\n        BNE far\n        .org $0700\nfar:    RTS",
+            2,
+            "branch to $0700 is out of range",
+        ),
+        ("        LDA #$100", 1, "$0100 does not fit in a byte"),
+        ("        .byte 1, 256", 1, "$0100 does not fit in a byte"),
+        ("x:      NOP\nx:      NOP", 2, "'x' is defined twice"),
+        ("        NOP\n        JMP nowhere", 2, "undefined label in 'nowhere'"),
+        ("        FOO #1", 1, "unknown instruction 'FOO'"),
+    ];
+    for (source, line, message) in cases {
+        assert_eq!(error(source), AsmError { line, message: message.to_string() }, "{}", source);
+    }
+    assert_eq!(error("        FOO #1").to_string(), "line 1: unknown instruction 'FOO'");
+}
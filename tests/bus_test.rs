@@ -0,0 +1,91 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use redNES::bus::{Bus, BusEvent};
+use redNES::cartridge::{Mirroring, Rom, RomHeader};
+use redNES::cpu::{Memory, CPU};
+use redNES::joypad::JoypadButton;
+
+// NROM-128 with `program` at $C000 (mirrored at $8000) and the reset vector pointing at it
+fn bus_with(program: &[u8]) -> Bus {
+    let mut prg_rom = vec![0xEA; 0x4000];
+    prg_rom[..program.len()].copy_from_slice(program);
+    prg_rom[0x3FFC] = 0x00;
+    prg_rom[0x3FFD] = 0xC0;
+    Bus::new(Rom {
+        prg_rom,
+        chr_rom: vec![0; 0x2000],
+        mapper: 0,
+        screen_mirroring: Mirroring::HORIZONTAL,
+        battery: false,
+        header: RomHeader::default(),
+    })
+    .unwrap()
+}
+
+#[test]
+fn ram_and_prg_ram_are_mapped() {
+    let mut bus = bus_with(&[]);
+    bus.mem_write(0x0012, 0x34);
+    assert_eq!(bus.mem_read(0x1812), 0x34); // mirrored every 2 KiB
+
+    bus.mem_write(0x6000, 0x56);
+    bus.mem_write(0x7FFF, 0x78);
+    assert_eq!(bus.mem_read(0x6000), 0x56);
+    assert_eq!(bus.mem_read(0x7FFF), 0x78);
+}
+
+#[test]
+fn unmapped_reads_return_open_bus() {
+    let mut bus = bus_with(&[]);
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let sink = Rc::clone(&events);
+    bus.set_log_sink(move |event| sink.borrow_mut().push(event));
+
+    bus.mem_write(0x0000, 0x5A);
+    assert_eq!(bus.mem_read(0x0000), 0x5A);
+    assert_eq!(bus.mem_read(0x5000), 0x5A);
+    assert_eq!(bus.mem_read(0x4000), 0x5A); // write-only APU register
+    bus.mem_write(0x4018, 0x11);
+
+    assert_eq!(
+        *events.borrow(),
+        vec![BusEvent::UnmappedRead(0x5000), BusEvent::UnmappedWrite(0x4018, 0x11)]
+    );
+}
+
+#[test]
+fn controllers_shift_out_buttons() {
+    let mut bus = bus_with(&[]);
+    bus.joypad(0).set_button_pressed_status(JoypadButton::BUTTON_A, true);
+    bus.joypad(0).set_button_pressed_status(JoypadButton::START, true);
+
+    bus.mem_write(0x4016, 1);
+    bus.mem_write(0x4016, 0);
+    let bits: Vec<u8> = (0..10).map(|_| bus.mem_read(0x4016) & 1).collect();
+    assert_eq!(bits, vec![1, 0, 0, 1, 0, 0, 0, 0, 1, 1]);
+
+    // the upper bits are left over from the last value on the bus, here the $40 of the address
+    let mut cpu = CPU::new(bus_with(&[0xAD, 0x16, 0x40])); // LDA $4016
+    cpu.reset();
+    cpu.step().unwrap();
+    assert_eq!(cpu.acc, 0x40);
+}
+
+#[test]
+fn oam_dma_copies_a_page_and_stalls_the_cpu() {
+    // LDA #$02; STA $4014
+    let mut cpu = CPU::new(bus_with(&[0xA9, 0x02, 0x8D, 0x14, 0x40]));
+    cpu.reset();
+    for i in 0..=255u16 {
+        cpu.mem_write(0x0200 + i, i as u8);
+    }
+
+    cpu.step().unwrap();
+    let result = cpu.step().unwrap();
+    assert!(result.cycles == 4 + 513 || result.cycles == 4 + 514, "{} cycles", result.cycles);
+    assert_eq!(cpu.bus.oam()[0], 0x00);
+    assert_eq!(cpu.bus.oam()[0x80], 0x80);
+    assert_eq!(cpu.bus.oam()[0xFF], 0xFF);
+    assert_eq!(cpu.pc, 0xC005);
+}
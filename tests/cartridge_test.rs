@@ -0,0 +1,98 @@
+use redNES::cartridge::{ConsoleType, HeaderFormat, Mirroring, Rom, Timing, VsSystem};
+use redNES::error::{EmulatorError, InvalidRomKind};
+
+// a header followed by `prg` bytes of PRG-ROM and `chr` bytes of CHR-ROM
+fn image(header: [u8; 16], prg: usize, chr: usize) -> Vec<u8> {
+    let mut raw = header.to_vec();
+    raw.extend(std::iter::repeat_n(0xAA, prg));
+    raw.extend(std::iter::repeat_n(0xBB, chr));
+    raw
+}
+
+#[test]
+fn ines_header() {
+    // mapper 4, two 16 KiB PRG banks, one 8 KiB CHR bank, vertical, battery
+    let header = [b'N', b'E', b'S', 0x1A, 2, 1, 0x43, 0x00, 0, 0, 0, 0, 0, 0, 0, 0];
+    let rom = Rom::new(&image(header, 0x8000, 0x2000)).unwrap();
+    assert_eq!(rom.mapper, 4);
+    assert_eq!(rom.screen_mirroring, Mirroring::VERTICAL);
+    assert!(rom.battery);
+    assert_eq!(rom.prg_rom.len(), 0x8000);
+    assert_eq!(rom.chr_rom, vec![0xBB; 0x2000]);
+
+    let header = rom.header;
+    assert_eq!(header.format, HeaderFormat::INes);
+    assert_eq!((header.mapper, header.submapper), (4, 0));
+    assert_eq!((header.prg_ram_size, header.prg_nvram_size), (0, 0x2000));
+    assert_eq!(header.chr_ram_size, 0);
+    assert_eq!(header.timing, Timing::Ntsc);
+    assert_eq!(header.console, ConsoleType::Nes);
+}
+
+#[test]
+fn nes2_header() {
+    let header = [
+        b'N', b'E', b'S', 0x1A,
+        0x02, // PRG-ROM: 2 x 16 KiB
+        0x00, // CHR-ROM: none
+        0x11, // mapper low nibble 1, vertical
+        0x19, // mapper bits 4-7 1, NES 2.0, Vs. System
+        0x31, // submapper 3, mapper bits 8-11 1
+        0x00,
+        0x70, // PRG-NVRAM 64 << 7 = 8 KiB
+        0x07, // CHR-RAM 64 << 7 = 8 KiB
+        0x01, // PAL
+        0x12, // Vs. hardware 1, Vs. PPU 2
+        0x01, // one misc ROM
+        0x01, // standard controllers
+    ];
+    let rom = Rom::new(&image(header, 0x8000, 0)).unwrap();
+    assert_eq!(rom.mapper, 0x111);
+    assert!(rom.chr_rom.is_empty());
+
+    let header = rom.header;
+    assert_eq!(header.format, HeaderFormat::Nes2);
+    assert_eq!((header.mapper, header.submapper), (0x111, 3));
+    assert_eq!((header.prg_rom_size, header.chr_rom_size), (0x8000, 0));
+    assert_eq!((header.prg_ram_size, header.prg_nvram_size), (0, 0x2000));
+    assert_eq!((header.chr_ram_size, header.chr_nvram_size), (0x2000, 0));
+    assert_eq!(header.timing, Timing::Pal);
+    assert_eq!(header.console, ConsoleType::VsSystem);
+    assert_eq!(header.vs_system, Some(VsSystem { ppu: 2, hardware: 1 }));
+    assert_eq!(header.misc_roms, 1);
+    assert_eq!(header.expansion_device, 1);
+}
+
+#[test]
+fn nes2_exponent_multiplier_sizes() {
+    // PRG-ROM 2^5 * 3 = 96 bytes, CHR-ROM through the 12-bit count: $100 x 8 KiB = 2 MiB
+    let mut header = [b'N', b'E', b'S', 0x1A, 0b0001_0101, 0x00, 0, 0x08, 0, 0x1F, 0, 0, 3, 0, 0, 0];
+    let rom = Rom::new(&image(header, 96, 0x200000)).unwrap();
+    assert_eq!(rom.prg_rom.len(), 96);
+    assert_eq!(rom.chr_rom.len(), 0x200000);
+    assert_eq!(rom.header.timing, Timing::Dendy);
+
+    // an exponent far too big to ever be backed by the file
+    header[4] = 0xFF;
+    header[9] = 0x0F;
+    assert!(matches!(Rom::new(&image(header, 0, 0)), Err(EmulatorError::TruncatedRom { .. })));
+}
+
+#[test]
+fn unknown_header_versions_are_rejected() {
+    let header = [b'N', b'E', b'S', 0x1A, 1, 1, 0, 0x04, 0, 0, 0, 0, 0, 0, 0, 0];
+    assert_eq!(
+        Rom::new(&image(header, 0x4000, 0x2000)).err(),
+        Some(EmulatorError::InvalidRom(InvalidRomKind::UnsupportedFormat))
+    );
+}
+
+#[test]
+fn images_without_prg_fail_to_load() {
+    let header = [b'N', b'E', b'S', 0x1A, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    let rom = Rom::new(&image(header, 0, 0x2000)).unwrap();
+    assert_eq!(
+        redNES::bus::Bus::new(rom).err().map(|err| err.to_string()),
+        Some("invalid ROM: no PRG ROM".to_string())
+    );
+}
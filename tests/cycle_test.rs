@@ -0,0 +1,144 @@
+use redNES::conformance::{BusAccess, BusCycle, RecordingMemory};
+use redNES::cpu::{Memory, CPU};
+use redNES::memory::FlatMemory;
+
+fn cpu_with(program: &[u8]) -> CPU<RecordingMemory> {
+    let mut memory = RecordingMemory::new();
+    for (i, byte) in program.iter().enumerate() {
+        memory.mem_write(0x0200 + i as u16, *byte);
+    }
+    memory.log.clear();
+
+    let mut cpu = CPU::new(memory);
+    cpu.pc = 0x0200;
+    cpu
+}
+
+fn read(address: u16, value: u8) -> BusCycle {
+    BusCycle { address, value, access: BusAccess::Read }
+}
+
+fn write(address: u16, value: u8) -> BusCycle {
+    BusCycle { address, value, access: BusAccess::Write }
+}
+
+#[test]
+fn read_modify_write_writes_the_old_value_first() {
+    // INC $10F0,X with X = $20 crosses into $1110
+    let mut cpu = cpu_with(&[0xFE, 0xF0, 0x10]);
+    cpu.index_x = 0x20;
+    cpu.bus.mem_write(0x1110, 0x41);
+    cpu.bus.log.clear();
+
+    let result = cpu.step().unwrap();
+    assert_eq!(result.cycles, 7);
+    assert_eq!(
+        cpu.bus.log,
+        vec![
+            read(0x0200, 0xFE),
+            read(0x0201, 0xF0),
+            read(0x0202, 0x10),
+            read(0x1010, 0x00), // high byte not yet carried
+            read(0x1110, 0x41),
+            write(0x1110, 0x41),
+            write(0x1110, 0x42),
+        ]
+    );
+}
+
+#[test]
+fn indexed_reads_only_pay_for_a_page_cross() {
+    // LDA $10F0,Y
+    let mut cpu = cpu_with(&[0xB9, 0xF0, 0x10, 0xB9, 0xF0, 0x10]);
+    cpu.index_y = 0x0F;
+    assert_eq!(cpu.step().unwrap().cycles, 4);
+
+    cpu.index_y = 0x10;
+    cpu.bus.log.clear();
+    assert_eq!(cpu.step().unwrap().cycles, 5);
+    assert_eq!(cpu.bus.log[3], read(0x1000, 0x00));
+    assert_eq!(cpu.bus.log[4], read(0x1100, 0x00));
+}
+
+#[test]
+fn taken_branch_fetches_the_next_opcode() {
+    // BNE +$7F from $0200 lands on $0281, same page
+    let mut cpu = cpu_with(&[0xD0, 0x7F, 0xEA]);
+    cpu.status = 0x24;
+
+    let result = cpu.step().unwrap();
+    assert_eq!(result.cycles, 3);
+    assert_eq!(result.pc, 0x0281);
+    assert_eq!(cpu.bus.log, vec![read(0x0200, 0xD0), read(0x0201, 0x7F), read(0x0202, 0xEA)]);
+}
+
+#[test]
+fn jumps_can_land_on_their_own_operand() {
+    // BNE -1, JMP $0201 and JSR $0201 all continue at the byte after the opcode
+    for program in [[0xD0, 0xFF, 0xEA], [0x4C, 0x01, 0x02], [0x20, 0x01, 0x02]] {
+        let mut cpu = cpu_with(&program);
+        cpu.status = 0x24;
+        assert_eq!(cpu.step().unwrap().pc, 0x0201, "{:02X?}", program);
+    }
+
+    // a branch not taken still skips its offset
+    let mut cpu = cpu_with(&[0xF0, 0xFF, 0xEA]);
+    cpu.status = 0x24;
+    assert_eq!(cpu.step().unwrap().pc, 0x0202);
+}
+
+#[test]
+fn jsr_pushes_between_operand_fetches() {
+    let mut cpu = cpu_with(&[0x20, 0x34, 0x12]);
+    cpu.step().unwrap();
+    assert_eq!(cpu.pc, 0x1234);
+    assert_eq!(
+        cpu.bus.log,
+        vec![
+            read(0x0200, 0x20),
+            read(0x0201, 0x34),
+            read(0x01FD, 0x00),
+            write(0x01FD, 0x02),
+            write(0x01FC, 0x02),
+            read(0x0202, 0x12),
+        ]
+    );
+}
+
+// counts the cpu cycles it is ticked for
+struct CountingMemory {
+    memory: FlatMemory,
+    ticks: u64,
+}
+
+impl Memory for CountingMemory {
+    fn mem_read(&mut self, address: u16) -> u8 {
+        self.memory.mem_read(address)
+    }
+
+    fn mem_write(&mut self, address: u16, value: u8) {
+        self.memory.mem_write(address, value);
+    }
+
+    fn peek(&self, address: u16) -> u8 {
+        self.memory.peek(address)
+    }
+
+    fn tick(&mut self) {
+        self.ticks += 1;
+    }
+}
+
+#[test]
+fn bus_is_ticked_once_per_cycle() {
+    let mut memory = FlatMemory::new();
+    // LDX #$05; loop: DEX; BNE loop; BRK
+    memory.load(0x0600, &[0xA2, 0x05, 0xCA, 0xD0, 0xFD, 0x00]);
+    let mut cpu = CPU::new(CountingMemory { memory, ticks: 0 });
+    cpu.pc = 0x0600;
+
+    cpu.run().unwrap();
+    assert_eq!(cpu.bus.ticks, cpu.cycles);
+    // 2 + 5 * 2 (DEX) + 4 * 3 + 2 (BNE) + 7 (BRK)
+    assert_eq!(cpu.cycles, 33);
+}
@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+
+use redNES::disasm::{self, Line};
+use redNES::opcodes;
+
+// address and text of each line, without labels
+fn texts(lines: &[Line]) -> Vec<(u16, String)> {
+    lines.iter().map(|line| (line.address, line.text(&BTreeMap::new()))).collect()
+}
+
+fn line(address: u16, bytes: &[u8], cmos: bool) -> Line {
+    let table = if cmos { &opcodes::CMOS_DECODE_TABLE } else { &opcodes::DECODE_TABLE };
+    Line { address, bytes: bytes.to_vec(), instruction: table[bytes[0] as usize] }
+}
+
+// $8000: a subroutine call, a jump over data and code ending in RTS and BRK, then padding
+const PROGRAM: [u8; 25] = [
+    0xA9, 0x01, // LDA #$01
+    0x20, 0x0A, 0x80, // JSR $800A
+    0x4C, 0x0E, 0x80, // JMP $800E
+    0xFF, 0xFF, // never reached
+    0xE8, // INX
+    0x60, // RTS
+    0x02, 0x02, // never reached
+    0x00, // BRK
+    0xEA, 0xEA, 0xEA, 0xEA, 0xEA, 0xEA, 0xEA, 0xEA, 0xEA, 0xEA, // never reached
+];
+
+#[test]
+fn branch_targets() {
+    // BNE to itself and forward
+    assert_eq!(line(0x0600, &[0xD0, 0xFE], false).target(), Some(0x0600));
+    assert_eq!(line(0x0600, &[0xD0, 0x10], false).target(), Some(0x0612));
+    // BBR0 $12 counts its offset from after the third byte
+    assert_eq!(line(0x0600, &[0x0F, 0x12, 0x05], true).target(), Some(0x0608));
+    assert_eq!(line(0x0600, &[0x0F, 0x12, 0xFD], true).target(), Some(0x0600));
+    assert_eq!(line(0x0600, &[0x0F, 0x12, 0xFD], true).text(&BTreeMap::new()), "BBR0 $12,$0600");
+
+    assert_eq!(line(0x0600, &[0x20, 0x34, 0x12], false).target(), Some(0x1234));
+    // an absolute read isn't a destination
+    assert_eq!(line(0x0600, &[0xAD, 0x34, 0x12], false).target(), None);
+}
+
+#[test]
+fn control_flow_stops_at_jmp_rts_and_brk() {
+    let lines = disasm::disassemble_from(&PROGRAM, 0x8000, &[0x8000]);
+    let code: Vec<u16> = lines
+        .iter()
+        .filter(|line| line.instruction.is_some())
+        .map(|line| line.address)
+        .collect();
+    assert_eq!(code, vec![0x8000, 0x8002, 0x8005, 0x800A, 0x800B, 0x800E]);
+}
+
+#[test]
+fn unreached_bytes_are_grouped_as_data() {
+    let lines = disasm::disassemble_from(&PROGRAM, 0x8000, &[0x8000]);
+    let data: Vec<(u16, String)> = lines
+        .iter()
+        .filter(|line| line.instruction.is_none())
+        .map(|line| (line.address, line.text(&BTreeMap::new())))
+        .collect();
+    assert_eq!(
+        data,
+        vec![
+            (0x8008, ".byte $FF, $FF".to_string()),
+            (0x800C, ".byte $02, $02".to_string()),
+            (0x800F, ".byte $EA, $EA, $EA, $EA, $EA, $EA, $EA, $EA".to_string()),
+            (0x8017, ".byte $EA, $EA".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn listing_labels_targets_and_vectors() {
+    let lines = disasm::disassemble_from(&PROGRAM, 0x8000, &[0x8000]);
+    let listing = disasm::format_listing(&lines, &[(0x8000, "RESET"), (0x800E, "NMI"), (0x800A, "IRQ")]);
+    assert_eq!(
+        listing,
+        concat!(
+            "RESET:\n",
+            "  8000  A9 01     LDA #$01\n",
+            "  8002  20 0A 80  JSR IRQ\n",
+            "  8005  4C 0E 80  JMP NMI\n",
+            "  8008            .byte $FF, $FF\n",
+            "IRQ:\n",
+            "  800A  E8        INX\n",
+            "  800B  60        RTS\n",
+            "  800C            .byte $02, $02\n",
+            "NMI:\n",
+            "  800E  00        BRK\n",
+            "  800F            .byte $EA, $EA, $EA, $EA, $EA, $EA, $EA, $EA\n",
+            "  8017            .byte $EA, $EA\n",
+        )
+    );
+
+    // targets without a name get a generated label
+    let listing = disasm::format_listing(&lines, &[]);
+    assert!(listing.contains("  8002  20 0A 80  JSR L_800A\n"));
+    assert!(listing.contains("L_800E:\n  800E  00        BRK\n"));
+}
+
+#[test]
+fn truncated_instructions_become_data() {
+    // NOP, then LDA abs and a zero page *NOP missing their operands
+    let bytes = [0xEA, 0xAD, 0x34];
+    assert_eq!(
+        texts(&disasm::disassemble(&bytes, 0x0600)),
+        vec![(0x0600, "NOP".to_string()), (0x0601, ".byte $AD".to_string()), (0x0602, ".byte $34".to_string())]
+    );
+    assert_eq!(
+        texts(&disasm::disassemble_from(&bytes, 0x0600, &[0x0600])),
+        vec![(0x0600, "NOP".to_string()), (0x0601, ".byte $AD, $34".to_string())]
+    );
+}
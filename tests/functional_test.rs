@@ -0,0 +1,105 @@
+use std::path::Path;
+
+use redNES::asm;
+use redNES::cpu::CpuVariant;
+use redNES::harness::{self, Outcome};
+
+const FIXTURE: &str = "tests/fixtures/6502_functional_test.bin";
+const MAX_INSTRUCTIONS: u64 = 100_000_000;
+
+fn image_from(source: &str) -> (Vec<u8>, asm::Program) {
+    let program = asm::assemble(source).expect("test program should assemble");
+    let mut image = vec![0; 0x10000];
+    for segment in &program.segments {
+        let start = segment.origin as usize;
+        image[start..start + segment.bytes.len()].copy_from_slice(&segment.bytes);
+    }
+    (image, program)
+}
+
+// the binary isn't checked in, so this only runs with `cargo test -- --ignored` once it has
+// been put in place (see tests/fixtures/README.md)
+#[test]
+#[ignore = "needs tests/fixtures/6502_functional_test.bin"]
+fn klaus_functional_test() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(FIXTURE);
+    let image = std::fs::read(&path).expect("6502_functional_test.bin should be in tests/fixtures");
+
+    let report = harness::run_test_image(
+        &image,
+        CpuVariant::Nmos6502,
+        harness::FUNCTIONAL_TEST_START,
+        harness::FUNCTIONAL_TEST_SUCCESS,
+        MAX_INSTRUCTIONS,
+    )
+    .unwrap();
+    assert!(
+        report.passed,
+        "functional test ended with {:x?} after {} instructions",
+        report.outcome, report.instructions
+    );
+}
+
+// a miniature self-checking test in the same style, so the harness and the flag logic it
+// exercises are covered even without the fixture
+const FLAG_CHECKS: &str = "
+        .org $0400
+start:  CLD
+        CLC
+        LDA #$7F
+        ADC #$01        ; signed overflow, no carry
+        BVC fail
+        BCS fail
+        BPL fail
+        CMP #$80
+        BNE fail
+        SEC
+        LDA #$00
+        SBC #$01        ; borrow clears carry
+        BCS fail
+        CMP #$FF
+        BNE fail
+        LDX #$10
+        CPX #$20        ; X < M clears carry and sets negative
+        BCS fail
+        BPL fail
+        LDY #$40
+        CPY #$40
+        BNE fail
+        BCC fail
+        JMP pass
+fail:   JMP fail
+pass:   JMP pass
+";
+
+#[test]
+fn harness_reports_success_trap() {
+    let (image, program) = image_from(FLAG_CHECKS);
+    let pass = program.label("pass").unwrap();
+
+    let report = harness::run_test_image(&image, CpuVariant::Nes2A03, 0x0400, pass, MAX_INSTRUCTIONS).unwrap();
+    assert_eq!(report.outcome, Outcome::Trapped(pass));
+    assert!(report.passed);
+}
+
+#[test]
+fn harness_reports_failure_trap() {
+    let (image, program) = image_from(
+        "
+        .org $0400
+        SEC
+        LDA #$01
+        ADC #$01        ; carry in makes this 3
+        CMP #$02
+        BEQ pass
+fail:   BNE fail
+pass:   JMP pass
+",
+    );
+    let fail = program.label("fail").unwrap();
+    let pass = program.label("pass").unwrap();
+
+    let report = harness::run_test_image(&image, CpuVariant::Nes2A03, 0x0400, pass, MAX_INSTRUCTIONS).unwrap();
+    assert_eq!(report.outcome, Outcome::Trapped(fail));
+    assert!(!report.passed);
+}
@@ -0,0 +1,187 @@
+use redNES::cpu::{Interrupt, Memory, StatusFlags, CPU};
+use redNES::memory::FlatMemory;
+
+const ORIGIN: u16 = 0x0600;
+const NMI_HANDLER: u16 = 0x0700;
+const IRQ_HANDLER: u16 = 0x0800;
+
+// `program` at $0600, with the NMI and IRQ/BRK vectors pointing at pages of NOPs
+fn memory_with(program: &[u8]) -> FlatMemory {
+    let mut memory = FlatMemory::new();
+    memory.load(ORIGIN, program);
+    memory.load(NMI_HANDLER, &[0xEA; 16]);
+    memory.load(IRQ_HANDLER, &[0xEA; 16]);
+    memory.mem_write_u16(0xFFFA, NMI_HANDLER);
+    memory.mem_write_u16(0xFFFE, IRQ_HANDLER);
+    memory
+}
+
+fn cpu_with(program: &[u8]) -> CPU<FlatMemory> {
+    let mut cpu = CPU::new(memory_with(program));
+    cpu.pc = ORIGIN;
+    cpu
+}
+
+// the status byte and return address an interrupt pushed, in that order on the stack
+fn pushed_state<M: Memory>(cpu: &CPU<M>) -> (u8, u16) {
+    let top = 0x0100 + cpu.sp as u16;
+    (cpu.bus.peek(top + 1), cpu.bus.peek_u16(top + 2))
+}
+
+// flat memory whose NMI output goes high after a number of cpu cycles
+struct LateNmi {
+    memory: FlatMemory,
+    cycles: u32,
+    raise_at: u32,
+}
+
+impl Memory for LateNmi {
+    fn mem_read(&mut self, address: u16) -> u8 {
+        self.memory.mem_read(address)
+    }
+
+    fn mem_write(&mut self, address: u16, value: u8) {
+        self.memory.mem_write(address, value);
+    }
+
+    fn peek(&self, address: u16) -> u8 {
+        self.memory.peek(address)
+    }
+
+    fn tick(&mut self) {
+        self.cycles += 1;
+    }
+
+    fn nmi_line(&self) -> bool {
+        self.cycles >= self.raise_at
+    }
+}
+
+#[test]
+fn nmi_is_edge_triggered() {
+    let mut cpu = cpu_with(&[0xEA; 16]);
+    cpu.set_nmi_line(true);
+
+    let result = cpu.step().unwrap();
+    assert_eq!(result.interrupt, Some(Interrupt::Nmi));
+    assert_eq!(result.cycles, 7);
+    assert_eq!(cpu.pc, NMI_HANDLER);
+    assert_eq!(pushed_state(&cpu).1, ORIGIN);
+
+    // holding the line doesn't interrupt again, only a new rising edge does
+    assert_eq!(cpu.step().unwrap().interrupt, None);
+    cpu.set_nmi_line(true);
+    assert_eq!(cpu.step().unwrap().interrupt, None);
+    cpu.set_nmi_line(false);
+    cpu.set_nmi_line(true);
+    assert_eq!(cpu.step().unwrap().interrupt, Some(Interrupt::Nmi));
+}
+
+#[test]
+fn nmi_ignores_the_interrupt_disable_flag() {
+    let mut cpu = cpu_with(&[0xEA; 16]);
+    assert!(cpu.status & StatusFlags::INTERRUPT_DISABLE != 0);
+    cpu.set_nmi_line(true);
+    assert_eq!(cpu.step().unwrap().interrupt, Some(Interrupt::Nmi));
+}
+
+#[test]
+fn irq_is_level_triggered_and_masked_by_i() {
+    // CLI; NOP; NOP
+    let mut cpu = cpu_with(&[0x58, 0xEA, 0xEA]);
+    cpu.set_irq_line(true);
+
+    // CLI only takes effect after the instruction that follows it
+    assert_eq!(cpu.step().unwrap().interrupt, None);
+    let result = cpu.step().unwrap();
+    assert_eq!((result.opcode, result.interrupt), (0xEA, None));
+
+    let result = cpu.step().unwrap();
+    assert_eq!(result.interrupt, Some(Interrupt::Irq));
+    assert_eq!(result.cycles, 7);
+    assert_eq!(cpu.pc, IRQ_HANDLER);
+    assert!(cpu.status & StatusFlags::INTERRUPT_DISABLE != 0);
+
+    // B is clear and the unused bit set in what a hardware interrupt pushes
+    let (flags, return_addr) = pushed_state(&cpu);
+    assert_eq!(flags & (StatusFlags::BREAK | StatusFlags::BREAK2), StatusFlags::BREAK2);
+    assert_eq!(flags & StatusFlags::INTERRUPT_DISABLE, 0);
+    assert_eq!(return_addr, ORIGIN + 2);
+
+    // the handler runs with I set even though the line is still held
+    assert_eq!(cpu.step().unwrap().interrupt, None);
+
+    // and the IRQ comes straight back once I is cleared while it is still asserted
+    cpu.status &= !StatusFlags::INTERRUPT_DISABLE;
+    cpu.step().unwrap();
+    assert_eq!(cpu.step().unwrap().interrupt, Some(Interrupt::Irq));
+
+    cpu.set_irq_line(false);
+    cpu.status &= !StatusFlags::INTERRUPT_DISABLE;
+    cpu.step().unwrap();
+    assert_eq!(cpu.step().unwrap().interrupt, None);
+}
+
+#[test]
+fn sei_still_lets_one_irq_through() {
+    // SEI; NOP
+    let mut cpu = cpu_with(&[0x78, 0xEA]);
+    cpu.status = StatusFlags::BREAK2;
+    cpu.step().unwrap();
+    cpu.set_irq_line(true);
+
+    // the poll during SEI saw I clear, so the IRQ is taken with I already set in the pushed flags
+    let result = cpu.step().unwrap();
+    assert_eq!(result.interrupt, Some(Interrupt::Irq));
+    assert!(pushed_state(&cpu).0 & StatusFlags::INTERRUPT_DISABLE != 0);
+}
+
+#[test]
+fn plp_clearing_i_is_one_instruction_late() {
+    // LDA #$20; PHA; PLP; NOP; NOP
+    let mut cpu = cpu_with(&[0xA9, 0x20, 0x48, 0x28, 0xEA, 0xEA]);
+    cpu.set_irq_line(true);
+    for _ in 0..3 {
+        assert_eq!(cpu.step().unwrap().interrupt, None);
+    }
+    assert!(cpu.status & StatusFlags::INTERRUPT_DISABLE == 0);
+
+    let result = cpu.step().unwrap();
+    assert_eq!((result.opcode, result.interrupt), (0xEA, None));
+    assert_eq!(cpu.step().unwrap().interrupt, Some(Interrupt::Irq));
+}
+
+#[test]
+fn brk_pushes_the_break_flag() {
+    // BRK, padding byte
+    let mut cpu = cpu_with(&[0x00, 0xFF]);
+    let result = cpu.step().unwrap();
+    assert_eq!(result.interrupt, Some(Interrupt::Break));
+    assert_eq!(result.cycles, 7);
+    assert_eq!(cpu.pc, IRQ_HANDLER);
+
+    let (flags, return_addr) = pushed_state(&cpu);
+    assert_eq!(flags & (StatusFlags::BREAK | StatusFlags::BREAK2), StatusFlags::BREAK | StatusFlags::BREAK2);
+    assert_eq!(return_addr, ORIGIN + 2);
+}
+
+#[test]
+fn nmi_during_brk_hijacks_its_vector() {
+    // the NMI rises while BRK is pushing, before it fetches the vector
+    let memory = LateNmi { memory: memory_with(&[0x00, 0xFF]), cycles: 0, raise_at: 3 };
+    let mut cpu = CPU::new(memory);
+    cpu.pc = ORIGIN;
+
+    let result = cpu.step().unwrap();
+    assert_eq!(result.interrupt, Some(Interrupt::Nmi));
+    assert_eq!(result.cycles, 7);
+    assert_eq!(cpu.pc, NMI_HANDLER);
+
+    // the pushed state is still BRK's
+    let (flags, return_addr) = pushed_state(&cpu);
+    assert!(flags & StatusFlags::BREAK != 0);
+    assert_eq!(return_addr, ORIGIN + 2);
+
+    // and the NMI isn't taken a second time
+    assert_eq!(cpu.step().unwrap().interrupt, None);
+}
@@ -0,0 +1,932 @@
+use redNES::cartridge::{Mirroring, Rom, RomHeader};
+use redNES::error::{EmulatorError, InvalidRomKind};
+use redNES::mapper::{self, Discrete, DiscreteBoard, Mapper, Mmc3, Mmc3Revision, Mmc5};
+
+// PRG banks of 16 KiB and CHR banks of 8 KiB, each filled with its own bank number
+fn rom(mapper: u16, prg_banks: usize, chr_banks: usize) -> Rom {
+    let prg_rom = (0..prg_banks).flat_map(|bank| vec![bank as u8; 0x4000]).collect();
+    let chr_rom = (0..chr_banks).flat_map(|bank| vec![bank as u8; 0x2000]).collect();
+    Rom {
+        prg_rom,
+        chr_rom,
+        mapper,
+        screen_mirroring: Mirroring::VERTICAL,
+        battery: true,
+        header: RomHeader::default(),
+    }
+}
+
+#[test]
+fn unsupported_mappers_are_rejected() {
+    match mapper::from_rom(rom(250, 1, 1)) {
+        Err(err) => {
+            assert_eq!(err, EmulatorError::UnsupportedMapper(250));
+            assert_eq!(err.to_string(), "unsupported mapper 250");
+        }
+        Ok(_) => panic!("mapper 250 should be rejected"),
+    }
+}
+
+#[test]
+fn roms_without_prg_are_rejected() {
+    for mapper in [0, 1, 4, 69] {
+        match mapper::from_rom(rom(mapper, 0, 1)) {
+            Err(err) => assert_eq!(err, EmulatorError::InvalidRom(InvalidRomKind::MissingPrgRom)),
+            Ok(_) => panic!("mapper {} accepted a ROM without PRG", mapper),
+        }
+    }
+}
+
+#[test]
+fn nrom_128_mirrors_its_bank() {
+    let mut rom = rom(0, 1, 1);
+    rom.prg_rom[0x1234] = 0xAB;
+    let mut board = mapper::from_rom(rom).unwrap();
+
+    assert_eq!(board.cpu_read(0x9234), Some(0xAB));
+    assert_eq!(board.cpu_read(0xD234), Some(0xAB));
+    assert_eq!(board.cpu_read(0x5000), None);
+    assert_eq!(board.mirroring(), Mirroring::VERTICAL);
+
+    // ROM ignores writes, PRG-RAM keeps them and is what gets saved
+    board.cpu_write(0x9234, 0x00);
+    assert_eq!(board.cpu_read(0x9234), Some(0xAB));
+    board.cpu_write(0x6010, 0x42);
+    assert_eq!(board.cpu_read(0x6010), Some(0x42));
+    assert_eq!(board.save_ram().map(|ram| ram[0x10]), Some(0x42));
+}
+
+#[test]
+fn nrom_256_and_chr_ram() {
+    let mut board = mapper::from_rom(rom(0, 2, 0)).unwrap();
+    assert_eq!(board.cpu_read(0x8000), Some(0));
+    assert_eq!(board.cpu_read(0xC000), Some(1));
+
+    board.ppu_write(0x1FFF, 0x99);
+    assert_eq!(board.ppu_read(0x1FFF), 0x99);
+
+    // CHR-ROM is read-only
+    let mut board = mapper::from_rom(rom(0, 1, 1)).unwrap();
+    board.ppu_write(0x0000, 0x99);
+    assert_eq!(board.ppu_read(0x0000), 0);
+}
+
+#[test]
+fn nrom_mirrors_4_kib_of_chr_rom() {
+    // a NES 2.0 header can declare half a bank of CHR
+    let mut rom = rom(0, 1, 0);
+    rom.chr_rom = (0..0x1000).map(|i| (i >> 8) as u8).collect();
+    let mut board = mapper::from_rom(rom).unwrap();
+    assert_eq!(board.ppu_read(0x0800), 0x08);
+    assert_eq!(board.ppu_read(0x1800), 0x08);
+}
+
+// loads an MMC1 register through the serial port, one cpu cycle apart from the last write
+fn mmc1_write(board: &mut dyn Mapper, address: u16, value: u8) {
+    for bit in 0..5 {
+        board.cpu_clock();
+        board.cpu_clock();
+        board.cpu_write(address, (value >> bit) & 1);
+    }
+}
+
+#[test]
+fn mmc1_prg_modes() {
+    let mut board = mapper::from_rom(rom(1, 8, 1)).unwrap();
+    // powers up with the last bank fixed at $C000
+    assert_eq!(board.cpu_read(0x8000), Some(0));
+    assert_eq!(board.cpu_read(0xC000), Some(7));
+
+    mmc1_write(board.as_mut(), 0xE000, 3);
+    assert_eq!(board.cpu_read(0x8000), Some(3));
+    assert_eq!(board.cpu_read(0xFFFF), Some(7));
+
+    // first bank fixed at $8000
+    mmc1_write(board.as_mut(), 0x8000, 0b01000);
+    assert_eq!(board.cpu_read(0x8000), Some(0));
+    assert_eq!(board.cpu_read(0xC000), Some(3));
+
+    // 32 KiB, the low bit of the bank is ignored
+    mmc1_write(board.as_mut(), 0x8000, 0b00000);
+    assert_eq!(board.cpu_read(0x8000), Some(2));
+    assert_eq!(board.cpu_read(0xC000), Some(3));
+
+    // a write with bit 7 set resets the shift register and goes back to fixing the last bank
+    board.cpu_write(0x8000, 1);
+    board.cpu_clock();
+    board.cpu_clock();
+    board.cpu_write(0x8000, 0x80);
+    assert_eq!(board.cpu_read(0x8000), Some(3));
+    assert_eq!(board.cpu_read(0xC000), Some(7));
+}
+
+#[test]
+fn mmc1_chr_banks_and_mirroring() {
+    let mut board = mapper::from_rom(rom(1, 2, 4)).unwrap();
+    mmc1_write(board.as_mut(), 0xA000, 5); // 4 KiB bank 5 is the upper half of 8 KiB bank 2
+    assert_eq!(board.ppu_read(0x0000), 2);
+    assert_eq!(board.ppu_read(0x1000), 2);
+
+    // 4 KiB mode, one-screen mirroring
+    mmc1_write(board.as_mut(), 0x8000, 0b11101);
+    mmc1_write(board.as_mut(), 0xC000, 6);
+    assert_eq!(board.ppu_read(0x0000), 2);
+    assert_eq!(board.ppu_read(0x1000), 3);
+    assert_eq!(board.mirroring(), Mirroring::SINGLE_SCREEN_UPPER);
+
+    mmc1_write(board.as_mut(), 0x8000, 0b11110);
+    assert_eq!(board.mirroring(), Mirroring::VERTICAL);
+    mmc1_write(board.as_mut(), 0x8000, 0b11111);
+    assert_eq!(board.mirroring(), Mirroring::HORIZONTAL);
+}
+
+#[test]
+fn mmc1_ignores_writes_on_consecutive_cycles() {
+    let mut board = mapper::from_rom(rom(1, 8, 1)).unwrap();
+    mmc1_write(board.as_mut(), 0xE000, 1);
+
+    // the two writes of a read-modify-write instruction only shift in one bit
+    for _ in 0..5 {
+        board.cpu_clock();
+        board.cpu_clock();
+        board.cpu_write(0xE000, 0);
+        board.cpu_clock();
+        board.cpu_write(0xE000, 1);
+    }
+    assert_eq!(board.cpu_read(0x8000), Some(0));
+}
+
+#[test]
+fn mmc1_prg_ram_can_be_disabled() {
+    let mut board = mapper::from_rom(rom(1, 2, 1)).unwrap();
+    board.cpu_write(0x6000, 0x42);
+    assert_eq!(board.cpu_read(0x6000), Some(0x42));
+
+    mmc1_write(board.as_mut(), 0xE000, 0x10);
+    assert_eq!(board.cpu_read(0x6000), None);
+    board.cpu_write(0x6000, 0x00);
+    mmc1_write(board.as_mut(), 0xE000, 0x00);
+    assert_eq!(board.cpu_read(0x6000), Some(0x42));
+}
+
+#[test]
+fn mmc1_surom_selects_the_prg_half_through_chr_bank_0() {
+    let mut board = mapper::from_rom(rom(1, 32, 0)).unwrap();
+    assert_eq!(board.cpu_read(0x8000), Some(0));
+    assert_eq!(board.cpu_read(0xC000), Some(15));
+
+    mmc1_write(board.as_mut(), 0xA000, 0x10);
+    mmc1_write(board.as_mut(), 0xE000, 2);
+    assert_eq!(board.cpu_read(0x8000), Some(18));
+    assert_eq!(board.cpu_read(0xC000), Some(31));
+}
+
+#[test]
+fn mmc3_prg_and_chr_banks() {
+    // 8 KiB PRG banks: 16 KiB bank n holds 8 KiB banks 2n and 2n + 1, both filled with n
+    let mut board = mapper::from_rom(rom(4, 8, 8)).unwrap();
+    board.cpu_write(0x8000, 6);
+    board.cpu_write(0x8001, 4);
+    board.cpu_write(0x8000, 7);
+    board.cpu_write(0x8001, 7);
+    assert_eq!(board.cpu_read(0x8000), Some(2));
+    assert_eq!(board.cpu_read(0xA000), Some(3));
+    assert_eq!(board.cpu_read(0xC000), Some(7)); // second-last bank
+    assert_eq!(board.cpu_read(0xE000), Some(7));
+
+    // PRG mode 1 fixes the second-last bank at $8000 instead
+    board.cpu_write(0x8000, 0x46);
+    assert_eq!(board.cpu_read(0x8000), Some(7));
+    assert_eq!(board.cpu_read(0xC000), Some(2));
+
+    // 1 KiB CHR banks: 8 KiB bank n holds 1 KiB banks 8n..8n + 7
+    board.cpu_write(0x8000, 0);
+    board.cpu_write(0x8001, 17); // low bit ignored, 2 KiB at $0000
+    board.cpu_write(0x8000, 2);
+    board.cpu_write(0x8001, 40);
+    assert_eq!(board.ppu_read(0x0000), 2);
+    assert_eq!(board.ppu_read(0x0400), 2);
+    assert_eq!(board.ppu_read(0x1000), 5);
+
+    // A12 inversion swaps the halves
+    board.cpu_write(0x8000, 0x80);
+    assert_eq!(board.ppu_read(0x1000), 2);
+    assert_eq!(board.ppu_read(0x0000), 5);
+}
+
+#[test]
+fn mmc3_mirroring_and_prg_ram_protect() {
+    let mut board = mapper::from_rom(rom(4, 2, 1)).unwrap();
+    board.cpu_write(0xA000, 1);
+    assert_eq!(board.mirroring(), Mirroring::HORIZONTAL);
+    board.cpu_write(0xA000, 0);
+    assert_eq!(board.mirroring(), Mirroring::VERTICAL);
+
+    board.cpu_write(0xA001, 0x80);
+    board.cpu_write(0x6000, 0x42);
+    board.cpu_write(0xA001, 0xC0); // write protected
+    board.cpu_write(0x6000, 0x00);
+    assert_eq!(board.cpu_read(0x6000), Some(0x42));
+    board.cpu_write(0xA001, 0x00);
+    assert_eq!(board.cpu_read(0x6000), None);
+}
+
+// one scanline of PPU fetches: background from $0000, then sprites from $1000
+fn mmc3_scanline(board: &mut dyn Mapper) {
+    for _ in 0..80 {
+        board.cpu_clock();
+        board.ppu_read(0x0000);
+    }
+    for _ in 0..8 {
+        board.cpu_clock();
+        board.ppu_read(0x1000);
+    }
+}
+
+#[test]
+fn mmc3_scanline_counter_raises_irq() {
+    let mut board = mapper::from_rom(rom(4, 2, 1)).unwrap();
+    board.cpu_write(0xC000, 3);
+    board.cpu_write(0xC001, 0);
+    board.cpu_write(0xE001, 0);
+
+    // the first clock reloads the counter, then three more count it down to 0
+    for _ in 0..3 {
+        mmc3_scanline(board.as_mut());
+        assert!(!board.irq_pending());
+    }
+    mmc3_scanline(board.as_mut());
+    assert!(board.irq_pending());
+
+    // writing $E000 acknowledges and disables
+    board.cpu_write(0xE000, 0);
+    assert!(!board.irq_pending());
+    for _ in 0..8 {
+        mmc3_scanline(board.as_mut());
+    }
+    assert!(!board.irq_pending());
+}
+
+#[test]
+fn mmc3_a12_rises_close_together_are_filtered() {
+    let mut board = mapper::from_rom(rom(4, 2, 1)).unwrap();
+    board.cpu_write(0xC000, 0);
+    board.cpu_write(0xE001, 0);
+    mmc3_scanline(board.as_mut());
+    board.cpu_write(0xE000, 0);
+    board.cpu_write(0xE001, 0);
+
+    board.cpu_clock();
+    board.ppu_read(0x0000);
+    board.ppu_read(0x1000);
+    assert!(!board.irq_pending());
+}
+
+#[test]
+fn mmc3_revisions_differ_on_a_latch_of_zero() {
+    let count_irqs = |mut board: Mmc3| {
+        board.cpu_write(0xC000, 0);
+        board.cpu_write(0xC001, 0);
+        board.cpu_write(0xE001, 0);
+        (0..4)
+            .filter(|_| {
+                mmc3_scanline(&mut board);
+                let pending = board.irq_pending();
+                board.cpu_write(0xE000, 0);
+                board.cpu_write(0xE001, 0);
+                pending
+            })
+            .count()
+    };
+
+    let new = Mmc3::new(rom(4, 2, 1));
+    assert_eq!(count_irqs(new), 4);
+    let old = Mmc3::new(rom(4, 2, 1)).with_revision(Mmc3Revision::A);
+    assert_eq!(count_irqs(old), 1);
+}
+
+#[test]
+fn uxrom_switches_the_low_bank() {
+    let mut board = mapper::from_rom(rom(2, 8, 0)).unwrap();
+    board.cpu_write(0x8000, 5);
+    assert_eq!(board.cpu_read(0x8000), Some(5));
+    assert_eq!(board.cpu_read(0xC000), Some(7));
+}
+
+#[test]
+fn uxrom_with_less_than_a_bank_of_prg() {
+    let mut rom = rom(2, 0, 0);
+    rom.prg_rom = (0..0x2000).map(|i| (i >> 8) as u8).collect();
+    let mut board = mapper::from_rom(rom).unwrap();
+    assert_eq!(board.cpu_read(0xC000), Some(0x00));
+    assert_eq!(board.cpu_read(0xFFFF), Some(0x1F));
+}
+
+#[test]
+fn latch_boards_switch_prg_and_chr() {
+    let mut cnrom = mapper::from_rom(rom(3, 2, 4)).unwrap();
+    cnrom.cpu_write(0xFFFF, 3);
+    assert_eq!(cnrom.ppu_read(0x0000), 3);
+    assert_eq!(cnrom.cpu_read(0xC000), Some(1));
+
+    // 32 KiB PRG bank n is made of 16 KiB banks 2n and 2n + 1
+    let mut gxrom = mapper::from_rom(rom(66, 8, 4)).unwrap();
+    gxrom.cpu_write(0x8000, 0x32);
+    assert_eq!(gxrom.cpu_read(0x8000), Some(6));
+    assert_eq!(gxrom.ppu_read(0x0000), 2);
+
+    let mut color_dreams = mapper::from_rom(rom(11, 8, 4)).unwrap();
+    color_dreams.cpu_write(0x8000, 0x32);
+    assert_eq!(color_dreams.cpu_read(0xC000), Some(5));
+    assert_eq!(color_dreams.ppu_read(0x0000), 3);
+
+    let mut bnrom = mapper::from_rom(rom(34, 8, 0)).unwrap();
+    bnrom.cpu_write(0x8000, 3);
+    assert_eq!(bnrom.cpu_read(0xFFFF), Some(7));
+}
+
+#[test]
+fn axrom_selects_a_single_screen() {
+    let mut board = mapper::from_rom(rom(7, 8, 0)).unwrap();
+    assert_eq!(board.mirroring(), Mirroring::SINGLE_SCREEN_LOWER);
+    board.cpu_write(0x8000, 0x12);
+    assert_eq!(board.cpu_read(0x8000), Some(4));
+    assert_eq!(board.mirroring(), Mirroring::SINGLE_SCREEN_UPPER);
+}
+
+#[test]
+fn bus_conflicts_and_the_written_value_with_rom() {
+    let mut prg = rom(2, 8, 0);
+    prg.prg_rom[7 * 0x4000] = 0x06; // the fixed bank at $C000
+    let mut board = Discrete::new(DiscreteBoard::UxRom, prg).with_bus_conflicts(true);
+    board.cpu_write(0xC000, 0x03);
+    assert_eq!(board.cpu_read(0x8000), Some(2));
+
+    let mut board = Discrete::new(DiscreteBoard::UxRom, rom(2, 8, 0));
+    board.cpu_write(0xC000, 0x03);
+    assert_eq!(board.cpu_read(0x8000), Some(3));
+}
+
+#[test]
+fn mmc2_prg_layout() {
+    // 8 KiB bank n is half of 16 KiB bank n / 2
+    let mut board = mapper::from_rom(rom(9, 8, 16)).unwrap();
+    board.cpu_write(0xA000, 5);
+    assert_eq!(board.cpu_read(0x8000), Some(2));
+    assert_eq!(board.cpu_read(0xA000), Some(6)); // last 24 KiB fixed
+    assert_eq!(board.cpu_read(0xC000), Some(7));
+
+    let mut board = mapper::from_rom(rom(10, 8, 16)).unwrap();
+    board.cpu_write(0xA000, 5);
+    assert_eq!(board.cpu_read(0x8000), Some(5));
+    assert_eq!(board.cpu_read(0xC000), Some(7));
+    board.cpu_write(0x6000, 0x42);
+    assert_eq!(board.cpu_read(0x6000), Some(0x42));
+}
+
+#[test]
+fn mmc2_tiles_fd_and_fe_flip_the_chr_latches() {
+    // 4 KiB bank n is half of 8 KiB bank n / 2
+    let mut board = mapper::from_rom(rom(9, 8, 16)).unwrap();
+    board.cpu_write(0xB000, 2); // $0000, $FD
+    board.cpu_write(0xC000, 4); // $0000, $FE
+    board.cpu_write(0xD000, 6); // $1000, $FD
+    board.cpu_write(0xE000, 8); // $1000, $FE
+    assert_eq!(board.ppu_read(0x0000), 2);
+    assert_eq!(board.ppu_read(0x1000), 4);
+
+    // the fetch that trips the latch still sees the old bank
+    assert_eq!(board.ppu_read(0x0FD8), 2);
+    assert_eq!(board.ppu_read(0x0000), 1);
+    board.ppu_read(0x1FDD);
+    assert_eq!(board.ppu_read(0x1000), 3);
+    board.ppu_read(0x1FE8);
+    assert_eq!(board.ppu_read(0x1000), 4);
+
+    // on the MMC2 only $0FD8/$0FE8 work for the first table, the MMC4 takes the whole row
+    board.ppu_read(0x0FEB);
+    assert_eq!(board.ppu_read(0x0000), 1);
+    let mut board = mapper::from_rom(rom(10, 8, 16)).unwrap();
+    board.cpu_write(0xB000, 2);
+    board.cpu_write(0xC000, 4);
+    board.ppu_read(0x0FDB);
+    assert_eq!(board.ppu_read(0x0000), 1);
+    board.ppu_read(0x0FEB);
+    assert_eq!(board.ppu_read(0x0000), 2);
+}
+
+#[test]
+fn mmc4_with_less_than_a_bank_of_prg() {
+    // a NES 2.0 header can declare PRG smaller than the 16 KiB fixed bank
+    let mut rom = rom(10, 0, 1);
+    rom.prg_rom = (0..0x2000).map(|i| (i >> 8) as u8).collect();
+    let board = mapper::from_rom(rom).unwrap();
+    assert_eq!(board.cpu_peek(0xC000), Some(0x00));
+    assert_eq!(board.cpu_peek(0xFFFF), Some(0x1F));
+}
+
+#[test]
+fn vrc_registers_follow_the_board_wiring() {
+    // VRC4c (mapper 21) selects its registers with A6/A7, VRC4e (mapper 23) with A2/A3
+    for (mapper, a, b) in [(21, 0x40, 0x80), (23, 0x04, 0x08)] {
+        let mut board = mapper::from_rom(rom(mapper, 8, 32)).unwrap();
+        board.cpu_write(0x8000, 4);
+        board.cpu_write(0xA000, 9);
+        // 8 KiB PRG bank n is half of 16 KiB bank n / 2
+        assert_eq!(board.cpu_read(0x8000), Some(2));
+        assert_eq!(board.cpu_read(0xA000), Some(4));
+        assert_eq!(board.cpu_read(0xC000), Some(7));
+
+        board.cpu_write(0x9000 | b, 0x02);
+        assert_eq!(board.cpu_read(0x8000), Some(7));
+        assert_eq!(board.cpu_read(0xC000), Some(2));
+
+        // CHR banks are written a nibble at a time; 1 KiB bank n is in 8 KiB bank n / 8
+        board.cpu_write(0xB000, 0x08);
+        board.cpu_write(0xB000 | a, 0x0A);
+        board.cpu_write(0xB000 | b, 0x0B);
+        board.cpu_write(0xB000 | a | b, 0x0C);
+        assert_eq!(board.ppu_read(0x0000), 0xA8 / 8);
+        assert_eq!(board.ppu_read(0x0400), 0xCB / 8);
+    }
+}
+
+#[test]
+fn vrc2a_drops_the_low_chr_bank_bit() {
+    let mut board = mapper::from_rom(rom(22, 2, 4)).unwrap();
+    // A1 selects the high nibble: bank 0 is $20, which the board turns into 1 KiB bank $10
+    board.cpu_write(0xB000, 0x00);
+    board.cpu_write(0xB002, 0x02);
+    assert_eq!(board.ppu_read(0x0000), 2);
+    board.cpu_write(0x9000, 1);
+    assert_eq!(board.mirroring(), Mirroring::HORIZONTAL);
+}
+
+#[test]
+fn vrc4_chr_banks_reach_past_256_kib() {
+    // VRC4f (A0 high nibble) with 512 KiB of CHR: $10 in the high nibble is 1 KiB bank 256,
+    // which is in 8 KiB bank 32
+    for (submapper, bank) in [(1, 32), (3, 0)] {
+        let mut rom = rom(23, 2, 64);
+        rom.header.submapper = submapper;
+        let mut board = mapper::from_rom(rom).unwrap();
+        board.cpu_write(0xB000, 0x00);
+        board.cpu_write(0xB001, 0x10);
+        // the VRC2b only has four high bits
+        assert_eq!(board.ppu_read(0x0000), bank, "submapper {}", submapper);
+    }
+}
+
+#[test]
+fn vrc4_irq_in_cycle_and_scanline_modes() {
+    let mut board = mapper::from_rom(rom(25, 2, 1)).unwrap();
+    // VRC4b: $F000 low nibble, $F002 (A1) high nibble, $F001 (A0) control
+    board.cpu_write(0xF000, 0x0);
+    board.cpu_write(0xF002, 0xF);
+    board.cpu_write(0xF001, 0x07);
+    for _ in 0..15 {
+        board.cpu_clock();
+    }
+    assert!(!board.irq_pending());
+    board.cpu_clock();
+    assert!(board.irq_pending());
+
+    // acknowledging keeps counting only if enable-after-ack was set
+    board.cpu_write(0xF003, 0);
+    assert!(!board.irq_pending());
+    for _ in 0..16 {
+        board.cpu_clock();
+    }
+    assert!(board.irq_pending());
+
+    // scanline mode: two scanlines of 113 2/3 cycles from $FE
+    board.cpu_write(0xF000, 0xE);
+    board.cpu_write(0xF001, 0x02);
+    for _ in 0..227 {
+        board.cpu_clock();
+    }
+    assert!(!board.irq_pending());
+    board.cpu_clock();
+    assert!(board.irq_pending());
+}
+
+#[test]
+fn mmc5_prg_modes_and_ram() {
+    let mut board = mapper::from_rom(rom(5, 8, 1)).unwrap();
+    // powers up in mode 3 with the last bank at $E000; 8 KiB bank n is in 16 KiB bank n / 2
+    assert_eq!(board.cpu_read(0xE000), Some(7));
+
+    board.cpu_write(0x5114, 0x83);
+    board.cpu_write(0x5115, 0x84);
+    board.cpu_write(0x5116, 0x01); // RAM bank 1
+    assert_eq!(board.cpu_read(0x8000), Some(1));
+    assert_eq!(board.cpu_read(0xA000), Some(2));
+
+    // RAM is write protected until $5102/$5103 are set to $02/$01
+    board.cpu_write(0xC000, 0x42);
+    assert_eq!(board.cpu_read(0xC000), Some(0));
+    board.cpu_write(0x5102, 0x02);
+    board.cpu_write(0x5103, 0x01);
+    board.cpu_write(0xC000, 0x42);
+    board.cpu_write(0x5113, 0x01);
+    assert_eq!(board.cpu_read(0x6000), Some(0x42));
+
+    // 32 KiB mode ignores the low two bits of $5117
+    board.cpu_write(0x5100, 0);
+    board.cpu_write(0x5117, 0x87);
+    assert_eq!(board.cpu_read(0x8000), Some(2));
+    assert_eq!(board.cpu_read(0xE000), Some(3));
+
+    // 16 KiB + 8 KiB + 8 KiB
+    board.cpu_write(0x5100, 2);
+    board.cpu_write(0x5115, 0x8B);
+    board.cpu_write(0x5116, 0x88);
+    board.cpu_write(0x5117, 0x8F);
+    assert_eq!(board.cpu_read(0x8000), Some(5));
+    assert_eq!(board.cpu_read(0xA000), Some(5));
+    assert_eq!(board.cpu_read(0xC000), Some(4));
+    assert_eq!(board.cpu_read(0xE000), Some(7));
+}
+
+#[test]
+fn mmc5_multiplier() {
+    let mut board = mapper::from_rom(rom(5, 2, 1)).unwrap();
+    board.cpu_write(0x5205, 200);
+    board.cpu_write(0x5206, 150);
+    assert_eq!(board.cpu_read(0x5205), Some((30000 & 0xFF) as u8));
+    assert_eq!(board.cpu_read(0x5206), Some((30000 >> 8) as u8));
+}
+
+#[test]
+fn mmc5_nametables_from_vram_exram_and_fill() {
+    let mut board = mapper::from_rom(rom(5, 2, 1)).unwrap();
+    let mut vram = [0u8; 0x800];
+    vram[0x0005] = 0x11;
+    vram[0x0405] = 0x22;
+
+    board.cpu_write(0x5104, 2);
+    board.cpu_write(0x5C05, 0x33);
+    assert_eq!(board.cpu_read(0x5C05), Some(0x33));
+    board.cpu_write(0x5104, 0);
+
+    // tables: page 1, page 0, ExRAM, fill
+    board.cpu_write(0x5105, 0b11_10_00_01);
+    board.cpu_write(0x5106, 0x44);
+    board.cpu_write(0x5107, 0x02);
+    assert_eq!(board.nametable_read(0x2005, &vram), 0x22);
+    assert_eq!(board.nametable_read(0x2405, &vram), 0x11);
+    assert_eq!(board.nametable_read(0x2805, &vram), 0x33);
+    assert_eq!(board.nametable_read(0x2C05, &vram), 0x44);
+    assert_eq!(board.nametable_read(0x2FC0, &vram), 0xAA);
+
+    board.nametable_write(0x2806, 0x55, &mut vram);
+    assert_eq!(board.nametable_read(0x2806, &vram), 0x55);
+}
+
+// what a rendering PPU reads for one scanline, from the nametable fetch of tile 2 to the two
+// dummy fetches that, with the first fetch of the next scanline, give the scanline away
+fn mmc5_scanline(board: &mut dyn Mapper, vram: &[u8]) -> Vec<u8> {
+    let mut reads = Vec::new();
+    let tile = |board: &mut dyn Mapper, x: u16, reads: &mut Vec<u8>| {
+        reads.push(board.nametable_read(0x2000 + x, vram));
+        reads.push(board.nametable_read(0x23C0 + x / 4, vram));
+        reads.push(board.ppu_read(0x0000));
+        reads.push(board.ppu_read(0x0008));
+    };
+    for x in 2..34 {
+        tile(board, x & 31, &mut reads);
+    }
+    for _ in 0..8 {
+        reads.push(board.nametable_read(0x2000, vram));
+        reads.push(board.nametable_read(0x2000, vram));
+        reads.push(board.ppu_read(0x1000));
+        reads.push(board.ppu_read(0x1008));
+    }
+    for x in 0..2 {
+        tile(board, x, &mut reads);
+    }
+    board.nametable_read(0x2002, vram);
+    board.nametable_read(0x2002, vram);
+    reads
+}
+
+fn mmc5_frame_start(board: &mut dyn Mapper, vram: &[u8]) {
+    board.ppu_register_write(0x2001, 0x18);
+    board.nametable_read(0x2002, vram);
+    board.nametable_read(0x2002, vram);
+}
+
+#[test]
+fn mmc5_scanline_irq() {
+    let mut board = mapper::from_rom(rom(5, 2, 1)).unwrap();
+    let vram = [0u8; 0x800];
+    board.cpu_write(0x5203, 3);
+    board.cpu_write(0x5204, 0x80);
+
+    mmc5_frame_start(board.as_mut(), &vram);
+    for _ in 0..3 {
+        mmc5_scanline(board.as_mut(), &vram);
+        assert!(!board.irq_pending());
+    }
+    mmc5_scanline(board.as_mut(), &vram);
+    assert!(board.irq_pending());
+
+    // reading the status acknowledges, and shows the PPU in the frame
+    assert_eq!(board.cpu_read(0x5204), Some(0xC0));
+    assert!(!board.irq_pending());
+
+    // vblank: no more PPU reads
+    for _ in 0..3 {
+        board.cpu_clock();
+    }
+    assert_eq!(board.cpu_read(0x5204), Some(0x00));
+}
+
+#[test]
+fn mmc5_8x16_sprites_use_their_own_chr_set() {
+    let mut board = mapper::from_rom(rom(5, 2, 4)).unwrap();
+    let vram = [0u8; 0x800];
+    // 1 KiB banks: bank n is in 8 KiB bank n / 8
+    board.cpu_write(0x5101, 3);
+    board.cpu_write(0x5120, 8); // set A, $0000
+    board.cpu_write(0x5124, 16); // set A, $1000
+    board.cpu_write(0x5128, 24); // set B, $0000 and $1000
+    board.ppu_register_write(0x2000, 0x20);
+
+    mmc5_frame_start(board.as_mut(), &vram);
+    let reads = mmc5_scanline(board.as_mut(), &vram);
+    assert_eq!(reads[2], 3); // background
+    assert_eq!(reads[128 + 2], 2); // sprite
+
+    // 8x8 sprites: the set written last is used for everything
+    board.ppu_register_write(0x2000, 0x00);
+    let reads = mmc5_scanline(board.as_mut(), &vram);
+    assert_eq!(reads[2], 3);
+    assert_eq!(reads[128 + 2], 3);
+}
+
+#[test]
+fn mmc5_extended_attributes() {
+    let mut board = Mmc5::new(rom(5, 2, 4));
+    let vram = [0u8; 0x800];
+    board.cpu_write(0x5104, 2);
+    board.cpu_write(0x5C02, 0xC5); // tile 2: palette 3, 4 KiB bank 5
+    board.cpu_write(0x5104, 1);
+
+    mmc5_frame_start(&mut board, &vram);
+    let reads = mmc5_scanline(&mut board, &vram);
+    assert_eq!(reads[1], 0xFF);
+    assert_eq!(reads[2], 2);
+    assert_eq!(reads[5], 0x00); // tile 3
+    assert_eq!(reads[6], 0);
+}
+
+#[test]
+fn mmc5_vertical_split() {
+    let mut board = Mmc5::new(rom(5, 2, 4));
+    let mut vram = [0u8; 0x800];
+    vram[0x0004] = 0x99;
+    board.cpu_write(0x5104, 2);
+    board.cpu_write(0x5C00 + 32 + 2, 0x77); // split row 1, tile 2
+    board.cpu_write(0x5104, 0);
+    board.cpu_write(0x5105, 0x44);
+    board.cpu_write(0x5200, 0x80 | 4); // left of tile 4
+    board.cpu_write(0x5201, 8);
+    board.cpu_write(0x5202, 7); // 4 KiB bank 7
+
+    mmc5_frame_start(&mut board, &vram);
+    let reads = mmc5_scanline(&mut board, &vram);
+    assert_eq!(reads[0], 0x77);
+    assert_eq!(reads[2], 3);
+    assert_eq!(reads[4 * 2], 0x99); // tile 4 is outside the split
+}
+
+#[test]
+fn fme7_banks_through_command_and_parameter() {
+    let mut board = mapper::from_rom(rom(69, 8, 4)).unwrap();
+    let command = |board: &mut dyn Mapper, command: u8, parameter: u8| {
+        board.cpu_write(0x8000, command);
+        board.cpu_write(0xA000, parameter);
+    };
+
+    // 8 KiB PRG bank n is in 16 KiB bank n / 2, 1 KiB CHR bank n in 8 KiB bank n / 8
+    command(board.as_mut(), 0x9, 3);
+    command(board.as_mut(), 0xB, 9);
+    command(board.as_mut(), 0x5, 17);
+    assert_eq!(board.cpu_read(0x8000), Some(1));
+    assert_eq!(board.cpu_read(0xC000), Some(4));
+    assert_eq!(board.cpu_read(0xE000), Some(7));
+    assert_eq!(board.ppu_read(0x1400), 2);
+
+    // $6000 maps ROM, then RAM once selected and enabled
+    command(board.as_mut(), 0x8, 0x04);
+    assert_eq!(board.cpu_read(0x6000), Some(2));
+    command(board.as_mut(), 0x8, 0x40);
+    assert_eq!(board.cpu_read(0x6000), None);
+    command(board.as_mut(), 0x8, 0xC0);
+    board.cpu_write(0x6000, 0x42);
+    assert_eq!(board.cpu_read(0x6000), Some(0x42));
+
+    command(board.as_mut(), 0xC, 3);
+    assert_eq!(board.mirroring(), Mirroring::SINGLE_SCREEN_UPPER);
+}
+
+#[test]
+fn fme7_irq_counts_down_cpu_cycles() {
+    let mut board = mapper::from_rom(rom(69, 2, 1)).unwrap();
+    board.cpu_write(0x8000, 0xE);
+    board.cpu_write(0xA000, 0x04);
+    board.cpu_write(0x8000, 0xF);
+    board.cpu_write(0xA000, 0x01);
+    board.cpu_write(0x8000, 0xD);
+    board.cpu_write(0xA000, 0x81);
+
+    // $0104 down to 0, then the wrap to $FFFF
+    for _ in 0..0x104 {
+        board.cpu_clock();
+    }
+    assert!(!board.irq_pending());
+    board.cpu_clock();
+    assert!(board.irq_pending());
+
+    // any write to the control register acknowledges
+    board.cpu_write(0xA000, 0x80);
+    assert!(!board.irq_pending());
+    for _ in 0..0x10000 {
+        board.cpu_clock();
+    }
+    assert!(!board.irq_pending());
+}
+
+#[test]
+fn fme7_with_less_than_a_bank_of_prg() {
+    let mut rom = rom(69, 0, 1);
+    rom.prg_rom = (0..0x1000).map(|i| (i >> 8) as u8).collect();
+    let mut board = mapper::from_rom(rom).unwrap();
+    assert_eq!(board.cpu_read(0xE000), Some(0x00));
+    assert_eq!(board.cpu_read(0xFFFF), Some(0x0F));
+}
+
+#[test]
+fn bandai_banks_and_irq() {
+    let mut board = mapper::from_rom(rom(16, 8, 4)).unwrap();
+    // 1 KiB CHR bank n is in 8 KiB bank n / 8
+    board.cpu_write(0x8008, 3);
+    board.cpu_write(0x8003, 17);
+    board.cpu_write(0x8009, 1);
+    assert_eq!(board.cpu_read(0x8000), Some(3));
+    assert_eq!(board.cpu_read(0xC000), Some(7));
+    assert_eq!(board.ppu_read(0x0C00), 2);
+    assert_eq!(board.mirroring(), Mirroring::HORIZONTAL);
+
+    // LZ93D50: the counter is loaded from the latch when the IRQ is enabled
+    board.cpu_write(0x800B, 0x10);
+    board.cpu_write(0x800C, 0x00);
+    board.cpu_write(0x800A, 0x01);
+    for _ in 0..15 {
+        board.cpu_clock();
+    }
+    assert!(!board.irq_pending());
+    board.cpu_clock();
+    assert!(board.irq_pending());
+    board.cpu_write(0x800A, 0x00);
+    assert!(!board.irq_pending());
+
+    // FCG: the same registers at $6000 write the counter directly
+    board.cpu_write(0x600B, 0x02);
+    board.cpu_write(0x600C, 0x00);
+    board.cpu_write(0x600A, 0x01);
+    board.cpu_clock();
+    board.cpu_clock();
+    assert!(board.irq_pending());
+}
+
+#[test]
+fn bandai_153_prg_ram_and_outer_bank() {
+    let mut board = mapper::from_rom(rom(153, 32, 0)).unwrap();
+    assert_eq!(board.cpu_read(0xC000), Some(15));
+    board.cpu_write(0x8000, 1);
+    board.cpu_write(0x8008, 2);
+    assert_eq!(board.cpu_read(0x8000), Some(18));
+    assert_eq!(board.cpu_read(0xC000), Some(31));
+
+    assert_eq!(board.cpu_read(0x6000), None);
+    board.cpu_write(0x800D, 0x20);
+    board.cpu_write(0x6000, 0x42);
+    assert_eq!(board.cpu_read(0x6000), Some(0x42));
+    assert_eq!(board.save_ram().map(|ram| ram[0]), Some(0x42));
+}
+
+// drives the EEPROM lines through $800D and samples SDA at $6000 while SCL is high
+fn i2c_bit(board: &mut dyn Mapper, bit: bool) -> bool {
+    let sda = (bit as u8) << 6;
+    board.cpu_write(0x800D, 0x80 | sda);
+    board.cpu_write(0x800D, 0x80 | sda | 0x20);
+    let line = board.cpu_read(0x6000).unwrap() & 0x10 != 0;
+    board.cpu_write(0x800D, 0x80 | sda);
+    line
+}
+
+fn i2c_start(board: &mut dyn Mapper) {
+    board.cpu_write(0x800D, 0xC0);
+    board.cpu_write(0x800D, 0xE0);
+    board.cpu_write(0x800D, 0xA0);
+    board.cpu_write(0x800D, 0x80);
+}
+
+fn i2c_stop(board: &mut dyn Mapper) {
+    board.cpu_write(0x800D, 0x80);
+    board.cpu_write(0x800D, 0xA0);
+    board.cpu_write(0x800D, 0xE0);
+}
+
+// true if the EEPROM acknowledged
+fn i2c_write(board: &mut dyn Mapper, byte: u8, msb_first: bool) -> bool {
+    for i in 0..8 {
+        let shift = if msb_first { 7 - i } else { i };
+        i2c_bit(board, (byte >> shift) & 1 != 0);
+    }
+    !i2c_bit(board, true)
+}
+
+fn i2c_read(board: &mut dyn Mapper, msb_first: bool, ack: bool) -> u8 {
+    let mut byte = 0;
+    for i in 0..8 {
+        let shift = if msb_first { 7 - i } else { i };
+        byte |= (i2c_bit(board, true) as u8) << shift;
+    }
+    i2c_bit(board, !ack);
+    byte
+}
+
+#[test]
+fn bandai_24c02_eeprom() {
+    let mut board = mapper::from_rom(rom(16, 2, 1)).unwrap();
+    let board = board.as_mut();
+    i2c_start(board);
+    assert!(i2c_write(board, 0xA0, true));
+    assert!(i2c_write(board, 0x10, true));
+    assert!(i2c_write(board, 0x5A, true));
+    assert!(i2c_write(board, 0xA5, true));
+    i2c_stop(board);
+    assert_eq!(&board.save_ram().unwrap()[0x10..0x12], &[0x5A, 0xA5]);
+
+    // random read: set the address with a dummy write, then start again to read
+    i2c_start(board);
+    assert!(i2c_write(board, 0xA0, true));
+    assert!(i2c_write(board, 0x10, true));
+    i2c_start(board);
+    assert!(i2c_write(board, 0xA1, true));
+    assert_eq!(i2c_read(board, true, true), 0x5A);
+    assert_eq!(i2c_read(board, true, false), 0xA5);
+    i2c_stop(board);
+
+    // other devices on the bus are not acknowledged
+    i2c_start(board);
+    assert!(!i2c_write(board, 0x50, true));
+    i2c_stop(board);
+}
+
+#[test]
+fn bandai_24c01_eeprom() {
+    let mut board = mapper::from_rom(rom(159, 2, 1)).unwrap();
+    let board = board.as_mut();
+    assert_eq!(board.save_ram().map(|eeprom| eeprom.len()), Some(128));
+
+    // address and R/W bit, LSB first
+    i2c_start(board);
+    assert!(i2c_write(board, 0x05, false));
+    assert!(i2c_write(board, 0x3C, false));
+    i2c_stop(board);
+
+    i2c_start(board);
+    assert!(i2c_write(board, 0x85, false));
+    assert_eq!(i2c_read(board, false, false), 0x3C);
+    i2c_stop(board);
+}
+
+#[test]
+fn nes2_submappers_pick_the_board() {
+    // VRC4a alone doesn't decode the A6/A7 lines of the VRC4c
+    let mut vrc4a = rom(21, 8, 32);
+    vrc4a.header.submapper = 1;
+    let mut board = mapper::from_rom(vrc4a).unwrap();
+    board.cpu_write(0x9080, 0x02);
+    assert_eq!(board.cpu_read(0xC000), Some(7));
+    board.cpu_write(0x9004, 0x02);
+    assert_eq!(board.cpu_read(0x8000), Some(7));
+
+    // submapper 2 of the discrete boards has bus conflicts
+    let mut uxrom = rom(2, 8, 0);
+    uxrom.header.submapper = 2;
+    uxrom.prg_rom[7 * 0x4000] = 0x06;
+    let mut board = mapper::from_rom(uxrom).unwrap();
+    board.cpu_write(0xC000, 0x03);
+    assert_eq!(board.cpu_read(0x8000), Some(2));
+
+    // mapper numbers above 255 only exist in NES 2.0
+    match mapper::from_rom(rom(300, 1, 1)) {
+        Err(err) => assert_eq!(err.to_string(), "unsupported mapper 300"),
+        Ok(_) => panic!("mapper 300 should be rejected"),
+    }
+}
@@ -0,0 +1,57 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use redNES::cartridge::{Mirroring, Rom, RomHeader};
+use redNES::mapper;
+use redNES::save;
+
+fn rom(mapper: u16, battery: bool) -> Rom {
+    Rom {
+        prg_rom: vec![0; 0x8000],
+        chr_rom: vec![0; 0x2000],
+        mapper,
+        screen_mirroring: Mirroring::VERTICAL,
+        battery,
+        header: RomHeader::default(),
+    }
+}
+
+// a fresh path under the system temp directory for each test
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("redNES-{}-{}.sav", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn save_path_sits_next_to_the_rom() {
+    assert_eq!(save::path_for(Path::new("roms/zelda.nes")), PathBuf::from("roms/zelda.sav"));
+}
+
+#[test]
+fn eeprom_contents_survive_a_store_and_load() {
+    let path = temp_path("eeprom");
+    let mut board = mapper::from_rom(rom(159, false)).unwrap();
+    board.save_ram_mut().unwrap()[0x20] = 0x77;
+    save::store(board.as_ref(), &path).unwrap();
+    assert_eq!(fs::read(&path).unwrap().len(), 128);
+
+    let mut board = mapper::from_rom(rom(159, false)).unwrap();
+    save::load(board.as_mut(), &path).unwrap();
+    assert_eq!(board.save_ram().unwrap()[0x20], 0x77);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn boards_without_save_memory_are_left_alone() {
+    let path = temp_path("none");
+    let mut board = mapper::from_rom(rom(0, false)).unwrap();
+    save::store(board.as_ref(), &path).unwrap();
+    assert!(!path.exists());
+
+    // and a board with a battery but no save yet starts blank
+    let mut board_with_battery = mapper::from_rom(rom(0, true)).unwrap();
+    save::load(board_with_battery.as_mut(), &path).unwrap();
+    assert_eq!(board_with_battery.cpu_read(0x6000), Some(0));
+    save::load(board.as_mut(), &path).unwrap();
+}
@@ -0,0 +1,51 @@
+use std::path::Path;
+
+use redNES::conformance;
+
+// SingleStepTests/65x02 "nes6502/v1" files, if checked out locally
+const FIXTURE_DIR: &str = "tests/fixtures/singlestep";
+
+const LDA_IMMEDIATE: &str = r#"[
+    {
+        "name": "a9 3c 1e",
+        "initial": { "pc": 512, "s": 253, "a": 0, "x": 0, "y": 0, "p": 38, "ram": [[512, 169], [513, 60]] },
+        "final": { "pc": 514, "s": 253, "a": 60, "x": 0, "y": 0, "p": 36, "ram": [[512, 169], [513, 60]] },
+        "cycles": [[512, 169, "read"], [513, 60, "read"]]
+    }
+]"#;
+
+#[test]
+fn runs_a_json_case() {
+    let cases = conformance::parse_cases(LDA_IMMEDIATE).unwrap();
+    assert_eq!(cases.len(), 1);
+
+    let result = conformance::run_cases(&cases, true);
+    assert_eq!(result.failed, 0, "{:?}", result.first_failure);
+    assert_eq!(result.passed, 1);
+}
+
+#[test]
+fn reports_mismatches() {
+    let json = LDA_IMMEDIATE.replace(r#""a": 60"#, r#""a": 61"#);
+    let cases = conformance::parse_cases(&json).unwrap();
+
+    let result = conformance::run_cases(&cases, false);
+    assert_eq!(result.failed, 1);
+    assert_eq!(
+        result.first_failure.as_deref(),
+        Some("a9 3c 1e: a: got $3C, expected $3D")
+    );
+}
+
+#[test]
+fn singlestep_suite() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(FIXTURE_DIR);
+    if !dir.exists() {
+        eprintln!("skipping: {} not found", FIXTURE_DIR);
+        return;
+    }
+
+    let results = conformance::run_directory(&dir, true).unwrap();
+    let failing = results.iter().flatten().filter(|result| result.failed > 0).count();
+    assert_eq!(failing, 0, "\n{}", conformance::format_matrix(&results));
+}
@@ -0,0 +1,72 @@
+use redNES::cpu::{CpuVariant, Memory, CPU};
+use redNES::memory::FlatMemory;
+use redNES::trace::trace;
+
+// a cpu at `pc` in nestest's starting state: 7 cycles in, P = $24, SP = $FD
+fn cpu_at(variant: CpuVariant, pc: u16, program: &[u8]) -> CPU<FlatMemory> {
+    let mut memory = FlatMemory::new();
+    memory.load(pc, program);
+    let mut cpu = CPU::with_variant(memory, variant);
+    cpu.pc = pc;
+    cpu.cycles = 7;
+    cpu
+}
+
+#[test]
+fn matches_the_first_nestest_line() {
+    let cpu = cpu_at(CpuVariant::Nes2A03, 0xC000, &[0x4C, 0xF5, 0xC5]);
+    assert_eq!(
+        trace(&cpu),
+        "C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7"
+    );
+}
+
+#[test]
+fn indirect_indexed_shows_the_pointer_and_effective_address() {
+    let mut cpu = cpu_at(CpuVariant::Nes2A03, 0x0600, &[0xB1, 0x33]);
+    cpu.bus.load(0x33, &[0x00, 0x04]);
+    cpu.bus.mem_write(0x0434, 0x89);
+    cpu.index_y = 0x34;
+    assert_eq!(
+        trace(&cpu),
+        "0600  B1 33     LDA ($33),Y = 0400 @ 0434 = 89  A:00 X:00 Y:34 P:24 SP:FD PPU:  0, 21 CYC:7"
+    );
+}
+
+#[test]
+fn unofficial_opcodes_are_starred() {
+    let mut cpu = cpu_at(CpuVariant::Nes2A03, 0x0600, &[0x04, 0xA9]);
+    cpu.bus.mem_write(0xA9, 0x5A);
+    assert_eq!(
+        trace(&cpu),
+        "0600  04 A9    *NOP $A9 = 5A                    A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7"
+    );
+
+    // the PPU column wraps onto the next scanline every 341 dots
+    let mut cpu = cpu_at(CpuVariant::Nes2A03, 0x0600, &[0xA7, 0x10]);
+    cpu.bus.mem_write(0x10, 0x80);
+    cpu.cycles = 341;
+    assert_eq!(
+        trace(&cpu),
+        "0600  A7 10    *LAX $10 = 80                    A:00 X:00 Y:00 P:24 SP:FD PPU:  3,  0 CYC:341"
+    );
+}
+
+#[test]
+fn indirect_jmp_wraps_the_page_only_on_nmos_parts() {
+    let setup = |variant| {
+        let mut cpu = cpu_at(variant, 0x0600, &[0x6C, 0xFF, 0x10]);
+        cpu.bus.mem_write(0x10FF, 0x34);
+        cpu.bus.mem_write(0x1100, 0x12);
+        cpu.bus.mem_write(0x1000, 0x56);
+        cpu
+    };
+    assert_eq!(
+        trace(&setup(CpuVariant::Nes2A03)),
+        "0600  6C FF 10  JMP ($10FF) = 5634              A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7"
+    );
+    assert_eq!(
+        trace(&setup(CpuVariant::Cmos65C02)),
+        "0600  6C FF 10  JMP ($10FF) = 1234              A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7"
+    );
+}
@@ -0,0 +1,169 @@
+use redNES::cpu::{CpuVariant, Memory, StatusFlags, CPU};
+use redNES::memory::FlatMemory;
+use redNES::opcodes;
+
+const ORIGIN: u16 = 0x0600;
+
+// a cpu about to run `program` from $0600, with A, X and the carry flag preset
+fn cpu_with(program: &[u8], acc: u8, index_x: u8, carry: bool) -> CPU<FlatMemory> {
+    let mut memory = FlatMemory::new();
+    memory.load(ORIGIN, program);
+    let mut cpu = CPU::new(memory);
+    cpu.pc = ORIGIN;
+    cpu.acc = acc;
+    cpu.index_x = index_x;
+    if carry {
+        cpu.status |= StatusFlags::CARRY;
+    }
+    cpu
+}
+
+fn flags(cpu: &CPU<FlatMemory>) -> u8 {
+    cpu.status & (StatusFlags::NEGATIVE | StatusFlags::OVERFLOW | StatusFlags::ZERO | StatusFlags::CARRY)
+}
+
+#[test]
+fn unofficial_opcodes_are_flagged_in_the_decode_table() {
+    let nes = opcodes::decode_table(CpuVariant::Nes2A03);
+    let unofficial = [
+        0xA7, 0x87, 0xC7, 0xE7, 0x07, 0x27, 0x47, 0x67, 0x0B, 0x4B, 0x6B, 0xCB, 0xEB, 0x1A, 0x80, 0x04, 0x0C,
+    ];
+    for opcode in unofficial {
+        assert!(nes[opcode].unwrap().unofficial, "${:02X}", opcode);
+    }
+    for opcode in [0xA5, 0x85, 0xC5, 0xE9, 0xEA, 0x4C] {
+        assert!(!nes[opcode].unwrap().unofficial, "${:02X}", opcode);
+    }
+
+    // the 65C02 gives the same opcodes official meanings
+    let cmos = opcodes::decode_table(CpuVariant::Cmos65C02);
+    assert!(!cmos[0xA7].unwrap().unofficial);
+    assert!(!cmos[0x1A].unwrap().unofficial);
+}
+
+#[test]
+fn lax_and_sax() {
+    // LAX $10
+    let mut cpu = cpu_with(&[0xA7, 0x10], 0, 0, false);
+    cpu.bus.mem_write(0x10, 0x80);
+    cpu.step().unwrap();
+    assert_eq!((cpu.acc, cpu.index_x), (0x80, 0x80));
+    assert_eq!(flags(&cpu), StatusFlags::NEGATIVE);
+
+    // SAX $10 stores A AND X and leaves the flags alone
+    let mut cpu = cpu_with(&[0x87, 0x10], 0xF0, 0x3C, false);
+    cpu.status |= StatusFlags::ZERO;
+    cpu.step().unwrap();
+    assert_eq!(cpu.bus.peek(0x10), 0x30);
+    assert_eq!(flags(&cpu), StatusFlags::ZERO);
+}
+
+#[test]
+fn read_modify_write_combinations() {
+    // (opcode, A, carry in, memory before) => (memory after, A, flags)
+    let cases = [
+        // DCP: decrement, then compare with A
+        (0xC7, 0x40, false, 0x41, 0x40, 0x40, StatusFlags::ZERO | StatusFlags::CARRY),
+        // ISB: increment, then subtract from A
+        (0xE7, 0x30, true, 0x0F, 0x10, 0x20, StatusFlags::CARRY),
+        // SLO: shift left, then OR into A
+        (0x07, 0x10, false, 0x81, 0x02, 0x12, StatusFlags::CARRY),
+        // RLA: rotate left, then AND into A
+        (0x27, 0xFF, false, 0x40, 0x80, 0x80, StatusFlags::NEGATIVE),
+        // SRE: shift right, then EOR into A
+        (0x47, 0x01, false, 0x03, 0x01, 0x00, StatusFlags::ZERO | StatusFlags::CARRY),
+        // RRA: rotate right, then add to A with the carry that fell out
+        (0x67, 0x10, true, 0x03, 0x81, 0x92, StatusFlags::NEGATIVE),
+    ];
+    for (opcode, acc, carry, before, after, result, expected) in cases {
+        let mut cpu = cpu_with(&[opcode, 0x10], acc, 0, carry);
+        cpu.bus.mem_write(0x10, before);
+        let step = cpu.step().unwrap();
+        assert_eq!(step.cycles, 5, "${:02X}", opcode);
+        assert_eq!(cpu.bus.peek(0x10), after, "${:02X}", opcode);
+        assert_eq!(cpu.acc, result, "${:02X}", opcode);
+        assert_eq!(flags(&cpu), expected, "${:02X}", opcode);
+    }
+}
+
+#[test]
+fn anc_and_alr() {
+    // ANC copies N into C
+    let mut cpu = cpu_with(&[0x0B, 0x80], 0xFF, 0, false);
+    cpu.step().unwrap();
+    assert_eq!(cpu.acc, 0x80);
+    assert_eq!(flags(&cpu), StatusFlags::NEGATIVE | StatusFlags::CARRY);
+
+    // ALR is AND then LSR
+    let mut cpu = cpu_with(&[0x4B, 0x03], 0xFF, 0, false);
+    cpu.step().unwrap();
+    assert_eq!(cpu.acc, 0x01);
+    assert_eq!(flags(&cpu), StatusFlags::CARRY);
+}
+
+#[test]
+fn arr_takes_c_and_v_from_bits_6_and_5() {
+    // (A AND operand, carry in) => (A, flags)
+    let cases = [
+        (0xFF, false, 0x7F, StatusFlags::CARRY),
+        (0xC0, true, 0xE0, StatusFlags::NEGATIVE | StatusFlags::CARRY),
+        (0x40, false, 0x20, StatusFlags::OVERFLOW),
+        (0x80, false, 0x40, StatusFlags::OVERFLOW | StatusFlags::CARRY),
+        (0x01, false, 0x00, StatusFlags::ZERO),
+    ];
+    for (value, carry, result, expected) in cases {
+        let mut cpu = cpu_with(&[0x6B, value], 0xFF, 0, carry);
+        cpu.step().unwrap();
+        assert_eq!(cpu.acc, result, "${:02X}", value);
+        assert_eq!(flags(&cpu), expected, "${:02X}", value);
+    }
+}
+
+#[test]
+fn axs_subtracts_without_borrow() {
+    // X = (A AND X) - operand, with C set when nothing was borrowed
+    let mut cpu = cpu_with(&[0xCB, 0x02], 0x0F, 0xF3, false);
+    cpu.step().unwrap();
+    assert_eq!((cpu.acc, cpu.index_x), (0x0F, 0x01));
+    assert_eq!(flags(&cpu), StatusFlags::CARRY);
+
+    // the incoming carry doesn't take part
+    let mut cpu = cpu_with(&[0xCB, 0x05], 0x0F, 0xF3, true);
+    cpu.step().unwrap();
+    assert_eq!(cpu.index_x, 0xFE);
+    assert_eq!(flags(&cpu), StatusFlags::NEGATIVE);
+
+    let mut cpu = cpu_with(&[0xCB, 0x03], 0x0F, 0xF3, false);
+    cpu.step().unwrap();
+    assert_eq!(cpu.index_x, 0x00);
+    assert_eq!(flags(&cpu), StatusFlags::ZERO | StatusFlags::CARRY);
+}
+
+#[test]
+fn sbc_eb_matches_the_official_sbc() {
+    let mut cpu = cpu_with(&[0xEB, 0x01], 0x00, 0, true);
+    cpu.step().unwrap();
+    assert_eq!(cpu.acc, 0xFF);
+    assert_eq!(flags(&cpu), StatusFlags::NEGATIVE);
+}
+
+#[test]
+fn nops_skip_their_operands() {
+    // (program, cycles, X) for implied, immediate, zero page, absolute and absolute,X forms
+    let cases: [(&[u8], u16, u8); 6] = [
+        (&[0x1A], 2, 0),
+        (&[0x80, 0xFF], 2, 0),
+        (&[0x04, 0x10], 3, 0),
+        (&[0x0C, 0x00, 0x10], 4, 0),
+        (&[0x1C, 0x00, 0x10], 4, 0x10),
+        (&[0x1C, 0xF0, 0x10], 5, 0x10),
+    ];
+    for (program, cycles, index_x) in cases {
+        let mut cpu = cpu_with(program, 0x42, index_x, false);
+        let status = cpu.status;
+        let step = cpu.step().unwrap();
+        assert_eq!(step.cycles, cycles, "{:02X?}", program);
+        assert_eq!(cpu.pc, ORIGIN + program.len() as u16, "{:02X?}", program);
+        assert_eq!((cpu.acc, cpu.index_x, cpu.status), (0x42, index_x, status));
+    }
+}
@@ -0,0 +1,171 @@
+use redNES::cpu::{CpuVariant, Memory, StatusFlags, CPU};
+use redNES::memory::FlatMemory;
+
+const ORIGIN: u16 = 0x0600;
+
+// loads `program` at $0600 and runs `steps` instructions of it
+fn run(variant: CpuVariant, program: &[u8], steps: usize) -> CPU<FlatMemory> {
+    let mut memory = FlatMemory::new();
+    memory.load(ORIGIN, program);
+    let mut cpu = CPU::with_variant(memory, variant);
+    cpu.pc = ORIGIN;
+    for _ in 0..steps {
+        cpu.step().unwrap();
+    }
+    cpu
+}
+
+fn flag(cpu: &CPU<FlatMemory>, flag: u8) -> bool {
+    cpu.status & flag != 0
+}
+
+#[test]
+fn nes_2a03_ignores_decimal_mode() {
+    // SED; CLC; LDA #$09; ADC #$01
+    let cpu = run(CpuVariant::Nes2A03, &[0xF8, 0x18, 0xA9, 0x09, 0x69, 0x01], 4);
+    assert_eq!(cpu.acc, 0x0A);
+}
+
+#[test]
+fn nmos_decimal_adc() {
+    // SED; CLC; LDA #$09; ADC #$01
+    let cpu = run(CpuVariant::Nmos6502, &[0xF8, 0x18, 0xA9, 0x09, 0x69, 0x01], 4);
+    assert_eq!(cpu.acc, 0x10);
+    assert!(!flag(&cpu, StatusFlags::CARRY));
+
+    // $99 + $01 wraps to $00 with carry, but N and Z come from the uncorrected sums
+    let cpu = run(CpuVariant::Nmos6502, &[0xF8, 0x18, 0xA9, 0x99, 0x69, 0x01], 4);
+    assert_eq!(cpu.acc, 0x00);
+    assert!(flag(&cpu, StatusFlags::CARRY));
+    assert!(!flag(&cpu, StatusFlags::ZERO));
+    assert!(flag(&cpu, StatusFlags::NEGATIVE));
+}
+
+#[test]
+fn nmos_decimal_sbc() {
+    // SED; SEC; LDA #$00; SBC #$01
+    let cpu = run(CpuVariant::Nmos6502, &[0xF8, 0x38, 0xA9, 0x00, 0xE9, 0x01], 4);
+    assert_eq!(cpu.acc, 0x99);
+    assert!(!flag(&cpu, StatusFlags::CARRY));
+
+    // SED; CLC; LDA #$46; SBC #$12 borrows one
+    let cpu = run(CpuVariant::Nmos6502, &[0xF8, 0x18, 0xA9, 0x46, 0xE9, 0x12], 4);
+    assert_eq!(cpu.acc, 0x33);
+    assert!(flag(&cpu, StatusFlags::CARRY));
+}
+
+#[test]
+fn cmos_decimal_sets_valid_flags_and_takes_a_cycle() {
+    // SED; CLC; LDA #$99; ADC #$01
+    let mut cpu = run(CpuVariant::Cmos65C02, &[0xF8, 0x18, 0xA9, 0x99, 0x69, 0x01], 3);
+    assert_eq!(cpu.step().unwrap().cycles, 3);
+    assert_eq!(cpu.acc, 0x00);
+    assert!(flag(&cpu, StatusFlags::CARRY));
+    assert!(flag(&cpu, StatusFlags::ZERO));
+    assert!(!flag(&cpu, StatusFlags::NEGATIVE));
+
+    // SED; SEC; LDA #$00; SBC #$01
+    let cpu = run(CpuVariant::Cmos65C02, &[0xF8, 0x38, 0xA9, 0x00, 0xE9, 0x01], 4);
+    assert_eq!(cpu.acc, 0x99);
+    assert!(flag(&cpu, StatusFlags::NEGATIVE));
+}
+
+#[test]
+fn jmp_indirect_page_wrap_is_fixed_on_cmos() {
+    // JMP ($10FF) with the pointer split across $10FF/$1100 and a decoy at $1000
+    let program = [0x6C, 0xFF, 0x10];
+    let setup = |variant| {
+        let mut memory = FlatMemory::new();
+        memory.load(ORIGIN, &program);
+        memory.load(0x10FF, &[0x34, 0x12]);
+        memory.load(0x1000, &[0x56]);
+        let mut cpu = CPU::with_variant(memory, variant);
+        cpu.pc = ORIGIN;
+        cpu
+    };
+
+    let mut nmos = setup(CpuVariant::Nmos6502);
+    assert_eq!(nmos.step().unwrap().cycles, 5);
+    assert_eq!(nmos.pc, 0x5634);
+
+    let mut cmos = setup(CpuVariant::Cmos65C02);
+    assert_eq!(cmos.step().unwrap().cycles, 6);
+    assert_eq!(cmos.pc, 0x1234);
+}
+
+#[test]
+fn cmos_new_instructions() {
+    // LDX #$42; PHX; PLY; STZ $10; BRA +1; (skipped); INC A
+    let program = [0xA2, 0x42, 0xDA, 0x7A, 0x64, 0x10, 0x80, 0x01, 0xEA, 0x1A];
+    let mut memory = FlatMemory::new();
+    memory.load(ORIGIN, &program);
+    memory.load(0x0010, &[0xFF]);
+    let mut cpu = CPU::with_variant(memory, CpuVariant::Cmos65C02);
+    cpu.pc = ORIGIN;
+    for _ in 0..6 {
+        cpu.step().unwrap();
+    }
+
+    assert_eq!(cpu.index_y, 0x42);
+    assert_eq!(cpu.peek(0x0010), 0x00);
+    assert_eq!(cpu.acc, 0x01);
+    assert_eq!(cpu.pc, ORIGIN + program.len() as u16);
+}
+
+#[test]
+fn cmos_bit_instructions() {
+    // LDA #$0F; TSB $20; TRB $21; RMB7 $22; SMB0 $22; BBS0 $22,+2
+    let program = [0xA9, 0x0F, 0x04, 0x20, 0x14, 0x21, 0x77, 0x22, 0x87, 0x22, 0x8F, 0x22, 0x02];
+    let mut memory = FlatMemory::new();
+    memory.load(ORIGIN, &program);
+    memory.load(0x0020, &[0xF0, 0xFF, 0x80]);
+    let mut cpu = CPU::with_variant(memory, CpuVariant::Cmos65C02);
+    cpu.pc = ORIGIN;
+
+    cpu.step().unwrap();
+    cpu.step().unwrap();
+    assert_eq!(cpu.peek(0x0020), 0xFF);
+    assert!(flag(&cpu, StatusFlags::ZERO)); // $0F & $F0
+
+    cpu.step().unwrap();
+    assert_eq!(cpu.peek(0x0021), 0xF0);
+    assert!(!flag(&cpu, StatusFlags::ZERO));
+
+    cpu.step().unwrap();
+    cpu.step().unwrap();
+    assert_eq!(cpu.peek(0x0022), 0x01);
+
+    cpu.step().unwrap();
+    assert_eq!(cpu.pc, ORIGIN + program.len() as u16 + 2);
+}
+
+#[test]
+fn cmos_interrupts_clear_decimal_mode() {
+    // SED; BRK
+    let mut memory = FlatMemory::new();
+    memory.load(ORIGIN, &[0xF8, 0x00]);
+    memory.load(0xFFFE, &[0x00, 0x80]);
+
+    let mut cmos = CPU::with_variant(memory, CpuVariant::Cmos65C02);
+    cmos.pc = ORIGIN;
+    cmos.step().unwrap();
+    cmos.step().unwrap();
+    assert_eq!(cmos.pc, 0x8000);
+    assert!(!flag(&cmos, StatusFlags::DECIMAL_MODE));
+
+    let nmos = run(CpuVariant::Nmos6502, &[0xF8, 0x00], 2);
+    assert!(flag(&nmos, StatusFlags::DECIMAL_MODE));
+}
+
+#[test]
+fn cmos_reserved_opcodes_are_nops() {
+    // $03 is a 1 cycle NOP, $02 a 2 byte NOP and $5C an 8 cycle one; on NMOS $02 jams
+    let mut cpu = run(CpuVariant::Cmos65C02, &[0x03, 0x02, 0xFF, 0x5C, 0x00, 0x00], 0);
+    assert_eq!(cpu.step().unwrap().cycles, 1);
+    assert_eq!(cpu.step().unwrap().cycles, 2);
+    assert_eq!(cpu.step().unwrap().cycles, 8);
+    assert_eq!(cpu.pc, ORIGIN + 6);
+
+    let mut nmos = run(CpuVariant::Nmos6502, &[0x02], 0);
+    assert!(nmos.step().is_err());
+}